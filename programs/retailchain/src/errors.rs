@@ -25,6 +25,12 @@ pub enum SupplyChainError {
     #[msg("This store is currently inactive")]
     StoreInactive,
     
+    #[msg("This product has been discontinued")]
+    ProductDiscontinued,
+    
+    #[msg("Only discontinued products can be removed from the catalog")]
+    ProductNotDiscontinued,
+    
     // Agreement Related Errors
     #[msg("Agreement is in an invalid status for this operation")]
     InvalidAgreementStatus,
@@ -128,6 +134,62 @@ pub enum SupplyChainError {
     #[msg("Required certification is missing or expired")]
     CertificationRequired,
     
+    // Shipment Tracking Errors
+    #[msg("Shipment has not passed its estimated arrival time")]
+    ShipmentNotOverdue,
+    
+    #[msg("The shipment exception is already resolved")]
+    ExceptionAlreadyResolved,
+    
+    #[msg("The shipment has unresolved exceptions")]
+    UnresolvedExceptions,
+    
+    #[msg("The status of this shipment is rolled up from its child shipments")]
+    ShipmentStatusRolledUp,
+    
+    #[msg("Delivery attestation signature is missing or invalid")]
+    InvalidSignature,
+    
+    // Account Versioning Errors
+    #[msg("Account is already at the current layout version")]
+    AccountAlreadyMigrated,
+    
+    #[msg("Account layout version is not supported by this program")]
+    UnsupportedAccountVersion,
+    
+    // Program Configuration Errors
+    #[msg("The program is paused")]
+    ProgramPaused,
+    
+    #[msg("This part of the program is paused")]
+    ModulePaused,
+    
+    #[msg("Fee exceeds the maximum allowed basis points")]
+    InvalidFeeBps,
+    
+    // Approval Errors
+    #[msg("This action needs approval from the store's approvers")]
+    ApprovalRequired,
+    
+    #[msg("Signer has already approved this action")]
+    AlreadyApproved,
+    
+    #[msg("Pending approval does not match the requested action")]
+    ApprovalMismatch,
+    
+    // Purchase Order Errors
+    #[msg("Purchase order is not in the required status")]
+    InvalidPurchaseOrderStatus,
+    
+    #[msg("Not enough catalog stock for the ordered quantity")]
+    InsufficientStock,
+    
+    #[msg("Shipment products do not match the purchase order lines")]
+    OrderLinesMismatch,
+    
+    #[msg("Catalog stock is still reserved for accepted purchase orders")]
+    StockReserved,
+    
     // Catch-all for unexpected errors
     #[msg("An unexpected error occurred")]
     UnexpectedError,
@@ -1,23 +1,31 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
 use crate::state::*;
 use crate::errors::SupplyChainError;
-use super::{validate_supplier_verified, validate_store_active};
+use super::{validate_string_length, validate_store_authority, validate_supplier_authority, validate_supplier_verified, has_store_role, has_supplier_role};
+use super::store_member::{StoreMember, STORE_ROLE_PURCHASING};
+use super::supplier_member::{SupplierMember, SUPPLIER_ROLE_CONTRACTS};
+use super::config::{ProgramConfig, Treasury, CONFIG_SEED, TREASURY_SEED};
+use super::approval::{consume_approval, PendingApproval, APPROVAL_ACTION_ACCEPT, APPROVAL_ACTION_FUND};
 
 // Supply agreement creation and management
 pub fn create_supply_agreement(
     ctx: Context<CreateAgreement>,
     terms: String,
     deadline: i64,
-    payment_amount: u64
+    payment_amount: u64,
+    nonce: u64,
+    required_certification: Option<u8>
 ) -> Result<()> {
     let agreement = &mut ctx.accounts.agreement;
     let current_time = Clock::get()?.unix_timestamp;
     
     // Validate inputs
     require!(
-        !terms.is_empty() && terms.len() <= 200,
+        !terms.is_empty(),
         SupplyChainError::InvalidData
     );
+    validate_string_length(&terms, MAX_TERMS_LEN)?;
     
     require!(
         deadline > current_time,
@@ -29,6 +37,14 @@ pub fn create_supply_agreement(
         SupplyChainError::InvalidPaymentAmount
     );
     
+    require!(
+        required_certification.map_or(true, |cert_type| cert_type <= CertificationType::Gmp as u8),
+        SupplyChainError::InvalidData
+    );
+    
+    // Only verified suppliers can enter new agreements
+    validate_supplier_verified(&ctx.accounts.supplier)?;
+    
     // Initialize the agreement
     agreement.supplier = ctx.accounts.supplier.key();
     agreement.store = ctx.accounts.store.key();
@@ -38,6 +54,12 @@ pub fn create_supply_agreement(
     agreement.status = AgreementStatus::Pending as u8;
     agreement.created_at = current_time;
     agreement.products = Vec::new();
+    agreement.nonce = nonce;
+    agreement.dispute_count = 0;
+    agreement.escrow_balance = 0;
+    agreement.set_required_certification(required_certification);
+    agreement.bump = ctx.bumps.agreement;
+    agreement.version = CURRENT_ACCOUNT_VERSION;
     
     // Emit agreement creation event
     emit!(AgreementCreatedEvent {
@@ -52,8 +74,8 @@ pub fn create_supply_agreement(
     Ok(())
 }
 
-pub fn accept_agreement(
-    ctx: Context<UpdateAgreement>
+pub fn accept_agreement<'info>(
+    ctx: Context<'_, '_, 'info, 'info, UpdateAgreement<'info>>
 ) -> Result<()> {
     let agreement = &mut ctx.accounts.agreement;
     let current_time = Clock::get()?.unix_timestamp;
@@ -65,10 +87,27 @@ pub fn accept_agreement(
     );
     
     // Only the store can accept an agreement
-    require!(
-        ctx.accounts.store.owner == ctx.accounts.authority.key(),
-        SupplyChainError::Unauthorized
-    );
+    validate_store_authority(
+        &ctx.accounts.authority,
+        &ctx.accounts.store,
+        &ctx.accounts.store_member,
+        STORE_ROLE_PURCHASING
+    )?;
+    
+    // Only verified suppliers can enter new agreements
+    validate_supplier_verified(&ctx.accounts.supplier)?;
+    
+    // High-value agreements need sign-off under the store's approval policy
+    consume_approval(
+        &ctx.accounts.store,
+        &ctx.accounts.pending_approval,
+        ctx.remaining_accounts,
+        agreement.key(),
+        APPROVAL_ACTION_ACCEPT,
+        agreement.payment_amount,
+        agreement.payment_amount,
+        ctx.accounts.authority.to_account_info()
+    )?;
     
     // Update status
     agreement.status = AgreementStatus::Active as u8;
@@ -99,11 +138,13 @@ pub fn add_products_to_agreement(
         SupplyChainError::InvalidAgreementStatus
     );
     
-    // Only supplier can add products
-    require!(
-        agreement.supplier == ctx.accounts.authority.key(),
-        SupplyChainError::Unauthorized
-    );
+    // Only the supplier can add products
+    validate_supplier_authority(
+        &ctx.accounts.authority,
+        &ctx.accounts.supplier,
+        &ctx.accounts.supplier_member,
+        SUPPLIER_ROLE_CONTRACTS
+    )?;
     
     // Add products to agreement
     for product in product_accounts.iter() {
@@ -112,6 +153,11 @@ pub fn add_products_to_agreement(
         }
     }
     
+    require!(
+        agreement.products.len() <= MAX_AGREEMENT_PRODUCTS,
+        SupplyChainError::ResourceLimitExceeded
+    );
+    
     // Emit products added event
     emit!(ProductsAddedToAgreementEvent {
         agreement: agreement.key(),
@@ -123,39 +169,174 @@ pub fn add_products_to_agreement(
     Ok(())
 }
 
-pub fn complete_agreement(
-    ctx: Context<CompleteAgreement>
+// The store escrows the payment in the agreement account itself, up to the agreed amount
+pub fn fund_agreement_escrow<'info>(
+    ctx: Context<'_, '_, 'info, 'info, FundAgreementEscrow<'info>>,
+    amount: u64
 ) -> Result<()> {
-    let agreement = &mut ctx.accounts.agreement;
     let current_time = Clock::get()?.unix_timestamp;
     
-    // Validate agreement status
     require!(
-        agreement.status == AgreementStatus::Active as u8,
+        ctx.accounts.agreement.status == AgreementStatus::Active as u8,
         SupplyChainError::InvalidAgreementStatus
     );
     
-    // Both parties must sign off for completion
-    let is_supplier = ctx.accounts.authority.key() == agreement.supplier;
-    let is_store_owner = ctx.accounts.authority.key() == ctx.accounts.store.owner;
+    let new_balance = ctx.accounts.agreement.escrow_balance.checked_add(amount)
+        .ok_or(SupplyChainError::ArithmeticError)?;
+    require!(
+        amount > 0 && new_balance <= ctx.accounts.agreement.payment_amount,
+        SupplyChainError::InvalidPaymentAmount
+    );
+    
+    consume_approval(
+        &ctx.accounts.store,
+        &ctx.accounts.pending_approval,
+        ctx.remaining_accounts,
+        ctx.accounts.agreement.key(),
+        APPROVAL_ACTION_FUND,
+        ctx.accounts.agreement.payment_amount,
+        amount,
+        ctx.accounts.authority.to_account_info()
+    )?;
+    
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.authority.to_account_info(),
+                to: ctx.accounts.agreement.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    
+    let agreement = &mut ctx.accounts.agreement;
+    agreement.escrow_balance = new_balance;
     
+    emit!(EscrowFundedEvent {
+        agreement: agreement.key(),
+        amount,
+        escrow_balance: new_balance,
+        funded_by: ctx.accounts.authority.key(),
+        timestamp: current_time,
+    });
+    
+    Ok(())
+}
+
+// Releases the whole escrow to the supplier; the protocol fee goes to the treasury.
+// Returns the amount paid to the supplier and the fee.
+fn release_escrow_to_supplier<'info>(
+    agreement: &mut Account<'info, SupplyAgreement>,
+    supplier_wallet: &AccountInfo<'info>,
+    treasury: &mut Account<'info, Treasury>,
+    config: &ProgramConfig,
+) -> Result<(u64, u64)> {
+    let amount = agreement.escrow_balance;
+    let fee = config.fee_for(amount)?;
+    let payout = amount - fee;
+    
+    agreement.sub_lamports(amount)?;
+    supplier_wallet.add_lamports(payout)?;
+    treasury.add_lamports(fee)?;
+    
+    agreement.escrow_balance = 0;
+    treasury.total_collected = treasury.total_collected.checked_add(fee)
+        .ok_or(SupplyChainError::ArithmeticError)?;
+    
+    Ok((payout, fee))
+}
+
+// Returns the whole escrow to the store owner; refunds are not charged a fee
+fn refund_escrow_to_store<'info>(
+    agreement: &mut Account<'info, SupplyAgreement>,
+    store_owner: &AccountInfo<'info>,
+) -> Result<u64> {
+    let amount = agreement.escrow_balance;
+    
+    agreement.sub_lamports(amount)?;
+    store_owner.add_lamports(amount)?;
+    agreement.escrow_balance = 0;
+    
+    Ok(amount)
+}
+
+// Checks who may complete an agreement and release its escrow. A verified delivery
+// linked to the agreement completes it without releasing the escrow; the supplier
+// can then settle it alone. An active agreement needs the store's purchasing
+// sign-off. Returns true if the agreement was already completed and only the
+// escrow is being settled.
+fn validate_completion(
+    agreement: &SupplyAgreement,
+    is_supplier: bool,
+    is_store_purchaser: bool,
+) -> Result<bool> {
+    let settling_only = agreement.status == AgreementStatus::Completed as u8 &&
+        agreement.escrow_balance > 0;
     require!(
-        is_supplier || is_store_owner,
+        agreement.status == AgreementStatus::Active as u8 || settling_only,
+        SupplyChainError::InvalidAgreementStatus
+    );
+    require!(
+        is_store_purchaser || (is_supplier && settling_only),
         SupplyChainError::Unauthorized
     );
+    Ok(settling_only)
+}
+
+pub fn complete_agreement(
+    ctx: Context<CompleteAgreement>
+) -> Result<()> {
+    let agreement = &mut ctx.accounts.agreement;
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    let is_supplier = agreement.supplier == ctx.accounts.supplier.key() && has_supplier_role(
+        &ctx.accounts.supplier,
+        &ctx.accounts.supplier_member,
+        ctx.accounts.authority.key(),
+        SUPPLIER_ROLE_CONTRACTS
+    );
+    let is_store_purchaser = has_store_role(
+        &ctx.accounts.store,
+        &ctx.accounts.store_member,
+        ctx.accounts.authority.key(),
+        STORE_ROLE_PURCHASING
+    );
+    
+    let settling_only = validate_completion(agreement, is_supplier, is_store_purchaser)?;
     
     // Update agreement state
+    let old_status = agreement.status;
     agreement.status = AgreementStatus::Completed as u8;
+    if !settling_only {
+        ctx.accounts.supplier.record_agreement_completed()?;
+    }
     
-    // Handle payment release from escrow if implemented
-    // This would require additional token accounts and logic
+    // Release the escrowed payment to the supplier
+    let (released_amount, fee_amount) = release_escrow_to_supplier(
+        agreement,
+        &ctx.accounts.supplier_wallet.to_account_info(),
+        &mut ctx.accounts.treasury,
+        &ctx.accounts.config,
+    )?;
     
     // Emit agreement completed event
-    emit!(AgreementStatusUpdatedEvent {
+    if !settling_only {
+        emit!(AgreementStatusUpdatedEvent {
+            agreement: agreement.key(),
+            old_status,
+            new_status: AgreementStatus::Completed as u8,
+            updated_by: ctx.accounts.authority.key(),
+            timestamp: current_time,
+        });
+    }
+    
+    emit!(AgreementSettledEvent {
         agreement: agreement.key(),
-        old_status: AgreementStatus::Active as u8,
-        new_status: AgreementStatus::Completed as u8,
-        updated_by: ctx.accounts.authority.key(),
+        recipient: ctx.accounts.supplier_wallet.key(),
+        released_amount,
+        fee_amount,
+        settled_by: ctx.accounts.authority.key(),
         timestamp: current_time,
     });
     
@@ -175,12 +356,24 @@ pub fn dispute_agreement(
         SupplyChainError::InvalidAgreementStatus
     );
     
+    validate_string_length(&dispute_reason, MAX_NOTES_LEN)?;
+    
     // Either party can raise a dispute
-    let is_supplier = ctx.accounts.authority.key() == agreement.supplier;
-    let is_store_owner = ctx.accounts.authority.key() == ctx.accounts.store.owner;
+    let is_supplier = agreement.supplier == ctx.accounts.supplier.key() && has_supplier_role(
+        &ctx.accounts.supplier,
+        &ctx.accounts.supplier_member,
+        ctx.accounts.authority.key(),
+        SUPPLIER_ROLE_CONTRACTS
+    );
+    let is_store_purchaser = has_store_role(
+        &ctx.accounts.store,
+        &ctx.accounts.store_member,
+        ctx.accounts.authority.key(),
+        STORE_ROLE_PURCHASING
+    );
     
     require!(
-        is_supplier || is_store_owner,
+        is_supplier || is_store_purchaser,
         SupplyChainError::Unauthorized
     );
     
@@ -194,6 +387,14 @@ pub fn dispute_agreement(
     dispute.reason = dispute_reason;
     dispute.created_at = current_time;
     dispute.resolved = false;
+    dispute.resolved_by = None;
+    dispute.resolution_notes = None;
+    dispute.resolved_at = None;
+    dispute.bump = ctx.bumps.dispute;
+    dispute.version = CURRENT_ACCOUNT_VERSION;
+    
+    agreement.dispute_count = agreement.dispute_count.checked_add(1)
+        .ok_or(SupplyChainError::ArithmeticError)?;
     
     // Emit dispute event
     emit!(AgreementDisputedEvent {
@@ -227,13 +428,27 @@ pub fn resolve_dispute(
         SupplyChainError::UnauthorizedVerifier
     );
     
+    // Each dispute settles the agreement once, while it is still disputed
+    require!(
+        !dispute.resolved,
+        SupplyChainError::DisputeAlreadyResolved
+    );
+    require!(
+        agreement.status == AgreementStatus::Disputed as u8,
+        SupplyChainError::InvalidAgreementStatus
+    );
+    
+    validate_string_length(&resolution_notes, MAX_NOTES_LEN)?;
+    
     // Update dispute status
     dispute.resolved = true;
     dispute.resolved_by = Some(ctx.accounts.authority.key());
     dispute.resolution_notes = Some(resolution_notes);
     dispute.resolved_at = Some(current_time);
     
-    // Update agreement based on resolution outcome
+    // Update agreement based on resolution outcome; completing pays the supplier,
+    // canceling refunds the store
+    let mut settlement: Option<(Pubkey, u64, u64)> = None;
     match resolution_outcome {
         0 => {
             // Continue agreement
@@ -242,23 +457,54 @@ pub fn resolve_dispute(
         1 => {
             // Complete agreement
             agreement.status = AgreementStatus::Completed as u8;
+            ctx.accounts.supplier.record_agreement_completed()?;
+            let (released_amount, fee_amount) = release_escrow_to_supplier(
+                agreement,
+                &ctx.accounts.supplier_wallet.to_account_info(),
+                &mut ctx.accounts.treasury,
+                &ctx.accounts.config,
+            )?;
+            settlement = Some((ctx.accounts.supplier_wallet.key(), released_amount, fee_amount));
         },
         2 => {
-            // Cancel agreement
+            // Cancel agreement; the supplier lost the dispute
             agreement.status = AgreementStatus::Canceled as u8;
+            ctx.accounts.supplier.record_dispute_lost()?;
+            let refunded_amount = refund_escrow_to_store(
+                agreement,
+                &ctx.accounts.store_owner.to_account_info(),
+            )?;
+            settlement = Some((ctx.accounts.store_owner.key(), refunded_amount, 0));
         },
         _ => return Err(SupplyChainError::InvalidData.into())
     }
     
+    let (released_amount, fee_amount) = settlement
+        .map(|(_, released, fee)| (released, fee))
+        .unwrap_or((0, 0));
+    
     // Emit resolution event
     emit!(DisputeResolvedEvent {
         dispute: dispute.key(),
         agreement: agreement.key(),
         resolved_by: ctx.accounts.authority.key(),
         outcome: resolution_outcome,
+        released_amount,
+        fee_amount,
         timestamp: current_time,
     });
     
+    if let Some((recipient, released_amount, fee_amount)) = settlement {
+        emit!(AgreementSettledEvent {
+            agreement: agreement.key(),
+            recipient,
+            released_amount,
+            fee_amount,
+            settled_by: ctx.accounts.authority.key(),
+            timestamp: current_time,
+        });
+    }
+    
     Ok(())
 }
 
@@ -276,11 +522,21 @@ pub fn cancel_agreement(
     );
     
     // Either party can cancel
-    let is_supplier = ctx.accounts.authority.key() == agreement.supplier;
-    let is_store_owner = ctx.accounts.authority.key() == ctx.accounts.store.owner;
+    let is_supplier = agreement.supplier == ctx.accounts.supplier.key() && has_supplier_role(
+        &ctx.accounts.supplier,
+        &ctx.accounts.supplier_member,
+        ctx.accounts.authority.key(),
+        SUPPLIER_ROLE_CONTRACTS
+    );
+    let is_store_purchaser = has_store_role(
+        &ctx.accounts.store,
+        &ctx.accounts.store_member,
+        ctx.accounts.authority.key(),
+        STORE_ROLE_PURCHASING
+    );
     
     require!(
-        is_supplier || is_store_owner,
+        is_supplier || is_store_purchaser,
         SupplyChainError::Unauthorized
     );
     
@@ -302,20 +558,33 @@ pub fn cancel_agreement(
 
 // Account contexts for agreement operations
 #[derive(Accounts)]
+#[instruction(terms: String, deadline: i64, payment_amount: u64, nonce: u64)]
 pub struct CreateAgreement<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
     #[account(
-        constraint = supplier.key == authority.key() || store.owner == authority.key()
+        constraint = has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_CONTRACTS) ||
+                    has_store_role(&store, &store_member, authority.key(), STORE_ROLE_PURCHASING)
     )]
     pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
     pub store: Account<'info, Store>,
+    pub store_member: Option<Account<'info, StoreMember>>,
     #[account(
         init,
         payer = authority,
-        space = SupplyAgreement::space()
+        space = SupplyAgreement::space(),
+        seeds = [
+            AGREEMENT_SEED,
+            store.key().as_ref(),
+            supplier.key().as_ref(),
+            &nonce.to_le_bytes()
+        ],
+        bump
     )]
     pub agreement: Account<'info, SupplyAgreement>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
     pub system_program: Program<'info, System>,
 }
 
@@ -325,12 +594,21 @@ pub struct UpdateAgreement<'info> {
     pub authority: Signer<'info>,
     #[account(
         mut,
-        constraint = (agreement.supplier == authority.key() && supplier.key() == authority.key()) || 
-                    (agreement.store == store.key() && store.owner == authority.key())
+        constraint = (agreement.supplier == supplier.key() &&
+                     has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_CONTRACTS)) || 
+                    (agreement.store == store.key() &&
+                     has_store_role(&store, &store_member, authority.key(), STORE_ROLE_PURCHASING))
     )]
     pub agreement: Account<'info, SupplyAgreement>,
     pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
     pub store: Account<'info, Store>,
+    pub store_member: Option<Account<'info, StoreMember>>,
+    // Required when accepting an agreement covered by the store's approval policy
+    #[account(mut)]
+    pub pending_approval: Option<Account<'info, PendingApproval>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
 }
 
 #[derive(Accounts)]
@@ -339,10 +617,34 @@ pub struct AddProductsToAgreement<'info> {
     pub authority: Signer<'info>,
     #[account(
         mut,
-        constraint = agreement.supplier == authority.key() && supplier.key() == authority.key()
+        constraint = agreement.supplier == supplier.key() &&
+                    has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_CONTRACTS)
     )]
     pub agreement: Account<'info, SupplyAgreement>,
     pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct FundAgreementEscrow<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = agreement.store == store.key() &&
+                    has_store_role(&store, &store_member, authority.key(), STORE_ROLE_PURCHASING)
+    )]
+    pub agreement: Account<'info, SupplyAgreement>,
+    pub store: Account<'info, Store>,
+    pub store_member: Option<Account<'info, StoreMember>>,
+    // Required when the agreement is covered by the store's approval policy
+    #[account(mut)]
+    pub pending_approval: Option<Account<'info, PendingApproval>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -351,12 +653,31 @@ pub struct CompleteAgreement<'info> {
     pub authority: Signer<'info>,
     #[account(
         mut,
-        constraint = (agreement.supplier == supplier.key() && supplier.key() == authority.key()) || 
-                    (agreement.store == store.key() && store.owner == authority.key())
+        constraint = (agreement.supplier == supplier.key() &&
+                     has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_CONTRACTS)) || 
+                    (agreement.store == store.key() &&
+                     has_store_role(&store, &store_member, authority.key(), STORE_ROLE_PURCHASING))
     )]
     pub agreement: Account<'info, SupplyAgreement>,
+    #[account(
+        mut,
+        constraint = agreement.supplier == supplier.key()
+    )]
     pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    /// CHECK: receives the escrow payout; address is checked against the supplier's key
+    #[account(mut, address = supplier.key)]
+    pub supplier_wallet: UncheckedAccount<'info>,
     pub store: Account<'info, Store>,
+    pub store_member: Option<Account<'info, StoreMember>>,
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
 }
 
 #[derive(Accounts)]
@@ -365,18 +686,30 @@ pub struct DisputeAgreement<'info> {
     pub authority: Signer<'info>,
     #[account(
         mut,
-        constraint = (agreement.supplier == supplier.key() && supplier.key() == authority.key()) || 
-                    (agreement.store == store.key() && store.owner == authority.key())
+        constraint = (agreement.supplier == supplier.key() &&
+                     has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_CONTRACTS)) || 
+                    (agreement.store == store.key() &&
+                     has_store_role(&store, &store_member, authority.key(), STORE_ROLE_PURCHASING))
     )]
     pub agreement: Account<'info, SupplyAgreement>,
     pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
     pub store: Account<'info, Store>,
+    pub store_member: Option<Account<'info, StoreMember>>,
     #[account(
         init,
         payer = authority,
-        space = AgreementDispute::space()
+        space = AgreementDispute::space(),
+        seeds = [
+            DISPUTE_SEED,
+            agreement.key().as_ref(),
+            &agreement.dispute_count.to_le_bytes()
+        ],
+        bump
     )]
     pub dispute: Account<'info, AgreementDispute>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
     pub system_program: Program<'info, System>,
 }
 
@@ -389,39 +722,67 @@ pub struct ResolveDispute<'info> {
         constraint = authority_credentials.is_verifier
     )]
     pub authority_credentials: Account<'info, VerifierCredential>,
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = !dispute.resolved @ SupplyChainError::DisputeAlreadyResolved
+    )]
     pub dispute: Account<'info, AgreementDispute>,
     #[account(
         mut,
-        constraint = dispute.agreement == agreement.key()
+        constraint = dispute.agreement == agreement.key(),
+        constraint = agreement.status == AgreementStatus::Disputed as u8 @ SupplyChainError::InvalidAgreementStatus
     )]
     pub agreement: Account<'info, SupplyAgreement>,
+    #[account(
+        mut,
+        constraint = agreement.supplier == supplier.key()
+    )]
+    pub supplier: Account<'info, Supplier>,
+    /// CHECK: receives the escrow payout; address is checked against the supplier's key
+    #[account(mut, address = supplier.key)]
+    pub supplier_wallet: UncheckedAccount<'info>,
+    #[account(
+        constraint = agreement.store == store.key()
+    )]
+    pub store: Account<'info, Store>,
+    /// CHECK: receives escrow refunds; address is checked against the store owner
+    #[account(mut, address = store.owner)]
+    pub store_owner: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
 }
 
 // Additional account structures
+// Disputes are addressed by ["dispute", agreement, dispute_index]
+pub const DISPUTE_SEED: &[u8] = b"dispute";
+
 #[account]
+#[derive(InitSpace)]
 pub struct AgreementDispute {
     pub agreement: Pubkey,
     pub initiated_by: Pubkey,
+    #[max_len(MAX_NOTES_LEN)]
     pub reason: String,
     pub created_at: i64,
     pub resolved: bool,
     pub resolved_by: Option<Pubkey>,
+    #[max_len(MAX_NOTES_LEN)]
     pub resolution_notes: Option<String>,
     pub resolved_at: Option<i64>,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
 }
 
 impl AgreementDispute {
     pub fn space() -> usize {
-        8 +    // discriminator
-        32 +   // agreement: Pubkey
-        32 +   // initiated_by: Pubkey
-        200 +  // reason: String (max assumed)
-        8 +    // created_at: i64
-        1 +    // resolved: bool
-        (1 + 32) +  // resolved_by: Option<Pubkey>
-        (1 + 200) + // resolution_notes: Option<String> (max assumed)
-        (1 + 8)     // resolved_at: Option<i64>
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
     }
 }
 
@@ -467,5 +828,72 @@ pub struct DisputeResolvedEvent {
     pub agreement: Pubkey,
     pub resolved_by: Pubkey,
     pub outcome: u8,
+    pub released_amount: u64,
+    pub fee_amount: u64,
     pub timestamp: i64,
 }
+
+#[event]
+pub struct EscrowFundedEvent {
+    pub agreement: Pubkey,
+    pub amount: u64,
+    pub escrow_balance: u64,
+    pub funded_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgreementSettledEvent {
+    pub agreement: Pubkey,
+    pub recipient: Pubkey,
+    pub released_amount: u64,
+    pub fee_amount: u64,
+    pub settled_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agreement_with(status: AgreementStatus, escrow_balance: u64) -> SupplyAgreement {
+        let mut agreement = SupplyAgreement::deserialize(
+            &mut vec![0u8; SupplyAgreement::space() - 8].as_slice()
+        ).unwrap();
+        agreement.status = status as u8;
+        agreement.escrow_balance = escrow_balance;
+        agreement
+    }
+
+    #[test]
+    fn supplier_cannot_complete_undelivered_agreement() {
+        let agreement = agreement_with(AgreementStatus::Active, 5_000_000);
+
+        let result = validate_completion(&agreement, true, false);
+
+        assert_eq!(result.err(), Some(SupplyChainError::Unauthorized.into()));
+    }
+
+    #[test]
+    fn supplier_can_settle_agreement_completed_by_delivery() {
+        let agreement = agreement_with(AgreementStatus::Completed, 5_000_000);
+
+        assert!(validate_completion(&agreement, true, false).unwrap());
+    }
+
+    #[test]
+    fn store_purchaser_can_complete_active_agreement() {
+        let agreement = agreement_with(AgreementStatus::Active, 5_000_000);
+
+        assert!(!validate_completion(&agreement, false, true).unwrap());
+    }
+
+    #[test]
+    fn settled_agreement_cannot_be_completed_again() {
+        let agreement = agreement_with(AgreementStatus::Completed, 0);
+
+        let result = validate_completion(&agreement, false, true);
+
+        assert_eq!(result.err(), Some(SupplyChainError::InvalidAgreementStatus.into()));
+    }
+}
@@ -0,0 +1,275 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::SupplyChainError;
+use super::has_store_role;
+use super::store_member::{StoreMember, STORE_ROLE_APPROVER};
+use super::config::{ProgramConfig, CONFIG_SEED};
+
+// M-of-N approval for high-value agreements.
+// A store's approval policy lives on the store itself; approvers are the owner and
+// staff members holding the approver role. Approvals for accepting or funding one
+// agreement collect in a pending-approval account, which the guarded instruction
+// consumes once enough approvers have signed.
+pub fn set_approval_policy(
+    ctx: Context<SetApprovalPolicy>,
+    approval_threshold: u64,
+    approvals_required: u8
+) -> Result<()> {
+    require!(
+        approvals_required as usize <= MAX_APPROVALS,
+        SupplyChainError::ResourceLimitExceeded
+    );
+
+    // A threshold without approvers would leave the policy silently disabled
+    require!(
+        approval_threshold == 0 || approvals_required > 0,
+        SupplyChainError::InvalidData
+    );
+
+    let store = &mut ctx.accounts.store;
+    store.approval_threshold = approval_threshold;
+    store.approvals_required = approvals_required;
+
+    emit!(ApprovalPolicyUpdatedEvent {
+        store: store.key(),
+        approval_threshold,
+        approvals_required,
+        updated_by: ctx.accounts.owner.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn approve_agreement_action(
+    ctx: Context<ApproveAgreementAction>,
+    action: u8,
+    amount: u64
+) -> Result<()> {
+    require!(
+        action == APPROVAL_ACTION_ACCEPT || action == APPROVAL_ACTION_FUND,
+        SupplyChainError::InvalidData
+    );
+
+    let approver = ctx.accounts.approver.key();
+    let current_time = Clock::get()?.unix_timestamp;
+    let pending = &mut ctx.accounts.pending_approval;
+
+    // A fresh (or fully revoked) request takes the amount of its first approval
+    if pending.approvals.is_empty() {
+        pending.store = ctx.accounts.store.key();
+        pending.agreement = ctx.accounts.agreement.key();
+        pending.action = action;
+        pending.amount = amount;
+        pending.created_at = current_time;
+        pending.bump = ctx.bumps.pending_approval;
+        pending.version = CURRENT_ACCOUNT_VERSION;
+    }
+
+    require!(
+        pending.amount == amount,
+        SupplyChainError::ApprovalMismatch
+    );
+    require!(
+        !pending.approvals.contains(&approver),
+        SupplyChainError::AlreadyApproved
+    );
+    require!(
+        pending.approvals.len() < MAX_APPROVALS,
+        SupplyChainError::ResourceLimitExceeded
+    );
+
+    pending.approvals.push(approver);
+
+    emit!(ApprovalRecordedEvent {
+        agreement: pending.agreement,
+        action,
+        amount,
+        approver,
+        approvals: pending.approvals.len() as u8,
+        approvals_required: ctx.accounts.store.approvals_required,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+pub fn revoke_agreement_approval(
+    ctx: Context<RevokeAgreementApproval>,
+    _action: u8
+) -> Result<()> {
+    let approver = ctx.accounts.approver.key();
+    let pending = &mut ctx.accounts.pending_approval;
+
+    let index = pending.approvals.iter().position(|key| *key == approver)
+        .ok_or(SupplyChainError::Unauthorized)?;
+    pending.approvals.remove(index);
+
+    emit!(ApprovalRevokedEvent {
+        agreement: pending.agreement,
+        action: pending.action,
+        approver,
+        approvals: pending.approvals.len() as u8,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Checks that an agreement action worth `agreement_value` has been approved under the
+/// store's policy, and closes the pending approval so it cannot be reused. Actions
+/// below the policy threshold pass without one. Approvals only count while the
+/// approver still holds the approver role: staff approvers' memberships are passed
+/// in `approver_memberships`.
+#[allow(clippy::too_many_arguments)]
+pub fn consume_approval<'info>(
+    store: &Store,
+    pending_approval: &Option<Account<'info, PendingApproval>>,
+    approver_memberships: &'info [AccountInfo<'info>],
+    agreement: Pubkey,
+    action: u8,
+    agreement_value: u64,
+    amount: u64,
+    rent_receiver: AccountInfo<'info>,
+) -> Result<()> {
+    if !store.requires_approval(agreement_value) {
+        return Ok(());
+    }
+
+    let pending = pending_approval.as_ref().ok_or(SupplyChainError::ApprovalRequired)?;
+    require!(
+        pending.agreement == agreement && pending.action == action && pending.amount == amount,
+        SupplyChainError::ApprovalMismatch
+    );
+
+    let memberships = approver_memberships.iter()
+        .map(Account::<StoreMember>::try_from)
+        .collect::<Result<Vec<_>>>()?;
+    let current_approvals = pending.approvals.iter()
+        .filter(|approver| {
+            **approver == store.owner ||
+                memberships.iter().any(|membership| membership.grants(pending.store, **approver, STORE_ROLE_APPROVER))
+        })
+        .count();
+    require!(
+        current_approvals >= store.approvals_required as usize,
+        SupplyChainError::ApprovalRequired
+    );
+
+    pending.close(rent_receiver)
+}
+
+// Account contexts for approval operations
+#[derive(Accounts)]
+pub struct SetApprovalPolicy<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        constraint = store.owner == owner.key() @ SupplyChainError::Unauthorized
+    )]
+    pub store: Account<'info, Store>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(action: u8)]
+pub struct ApproveAgreementAction<'info> {
+    #[account(mut)]
+    pub approver: Signer<'info>,
+    #[account(
+        constraint = has_store_role(&store, &approver_membership, approver.key(), STORE_ROLE_APPROVER)
+    )]
+    pub store: Account<'info, Store>,
+    pub approver_membership: Option<Account<'info, StoreMember>>,
+    #[account(
+        constraint = agreement.store == store.key()
+    )]
+    pub agreement: Account<'info, SupplyAgreement>,
+    #[account(
+        init_if_needed,
+        payer = approver,
+        space = PendingApproval::space(),
+        seeds = [PENDING_APPROVAL_SEED, agreement.key().as_ref(), &[action]],
+        bump
+    )]
+    pub pending_approval: Account<'info, PendingApproval>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(action: u8)]
+pub struct RevokeAgreementApproval<'info> {
+    pub approver: Signer<'info>,
+    pub agreement: Account<'info, SupplyAgreement>,
+    #[account(
+        mut,
+        seeds = [PENDING_APPROVAL_SEED, agreement.key().as_ref(), &[action]],
+        bump = pending_approval.bump
+    )]
+    pub pending_approval: Account<'info, PendingApproval>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// Events
+#[event]
+pub struct ApprovalPolicyUpdatedEvent {
+    pub store: Pubkey,
+    pub approval_threshold: u64,
+    pub approvals_required: u8,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ApprovalRecordedEvent {
+    pub agreement: Pubkey,
+    pub action: u8,
+    pub amount: u64,
+    pub approver: Pubkey,
+    pub approvals: u8,
+    pub approvals_required: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ApprovalRevokedEvent {
+    pub agreement: Pubkey,
+    pub action: u8,
+    pub approver: Pubkey,
+    pub approvals: u8,
+    pub timestamp: i64,
+}
+
+// Accounts
+// Pending approvals are addressed by ["pending_approval", agreement, action]
+pub const PENDING_APPROVAL_SEED: &[u8] = b"pending_approval";
+
+// Agreement actions that can require approval
+pub const APPROVAL_ACTION_ACCEPT: u8 = 0;
+pub const APPROVAL_ACTION_FUND: u8 = 1;
+
+#[account]
+#[derive(InitSpace)]
+pub struct PendingApproval {
+    pub store: Pubkey,
+    pub agreement: Pubkey,
+    pub action: u8,
+    // Accepted payment amount, or the escrow deposit being approved
+    pub amount: u64,
+    #[max_len(MAX_APPROVALS)]
+    pub approvals: Vec<Pubkey>,
+    pub created_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl PendingApproval {
+    pub fn space() -> usize {
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+}
@@ -0,0 +1,186 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::SupplyChainError;
+use super::config::{ProgramConfig, CONFIG_SEED};
+
+// Supplier certifications.
+// Certifications are issued and revoked by verifiers only, one account per supplier
+// and certification type. Reissuing renews the certification in place.
+pub fn issue_certification(
+    ctx: Context<IssueCertification>,
+    cert_type: u8,
+    expires_at: i64,
+    document_hash: [u8; 32]
+) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+
+    require!(
+        cert_type <= CertificationType::Gmp as u8,
+        SupplyChainError::InvalidData
+    );
+
+    require!(
+        expires_at > current_time,
+        SupplyChainError::InvalidTimestamp
+    );
+
+    let certification = &mut ctx.accounts.certification;
+    certification.supplier = ctx.accounts.supplier.key();
+    certification.cert_type = cert_type;
+    certification.issuer = ctx.accounts.authority.key();
+    certification.issued_at = current_time;
+    certification.expires_at = expires_at;
+    certification.document_hash = document_hash;
+    certification.revoked = false;
+    certification.bump = ctx.bumps.certification;
+    certification.version = CURRENT_ACCOUNT_VERSION;
+
+    emit!(CertificationIssuedEvent {
+        certification: certification.key(),
+        supplier: certification.supplier,
+        cert_type,
+        issuer: certification.issuer,
+        expires_at,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+pub fn revoke_certification(
+    ctx: Context<RevokeCertification>,
+    _cert_type: u8
+) -> Result<()> {
+    let certification = &mut ctx.accounts.certification;
+
+    require!(
+        !certification.revoked,
+        SupplyChainError::InvalidData
+    );
+
+    certification.revoked = true;
+
+    emit!(CertificationRevokedEvent {
+        certification: certification.key(),
+        supplier: certification.supplier,
+        cert_type: certification.cert_type,
+        revoked_by: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Validates that the supplier holds an unexpired, unrevoked certification of
+/// `cert_type`; a missing account fails the same way
+pub fn validate_certification(
+    certification: &Option<Account<Certification>>,
+    supplier: Pubkey,
+    cert_type: u8,
+    current_time: i64,
+) -> Result<()> {
+    require!(
+        certification.as_ref().is_some_and(|certification| {
+            certification.supplier == supplier &&
+                certification.cert_type == cert_type &&
+                certification.is_valid_at(current_time)
+        }),
+        SupplyChainError::CertificationRequired
+    );
+    Ok(())
+}
+
+// Account contexts for certification operations
+#[derive(Accounts)]
+#[instruction(cert_type: u8)]
+pub struct IssueCertification<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = authority_credentials.authority == authority.key(),
+        constraint = authority_credentials.is_verifier @ SupplyChainError::UnauthorizedVerifier
+    )]
+    pub authority_credentials: Account<'info, VerifierCredential>,
+    pub supplier: Account<'info, Supplier>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = Certification::space(),
+        seeds = [CERTIFICATION_SEED, supplier.key().as_ref(), &[cert_type]],
+        bump
+    )]
+    pub certification: Account<'info, Certification>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cert_type: u8)]
+pub struct RevokeCertification<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = authority_credentials.authority == authority.key(),
+        constraint = authority_credentials.is_verifier @ SupplyChainError::UnauthorizedVerifier
+    )]
+    pub authority_credentials: Account<'info, VerifierCredential>,
+    pub supplier: Account<'info, Supplier>,
+    #[account(
+        mut,
+        seeds = [CERTIFICATION_SEED, supplier.key().as_ref(), &[cert_type]],
+        bump = certification.bump
+    )]
+    pub certification: Account<'info, Certification>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// Events
+#[event]
+pub struct CertificationIssuedEvent {
+    pub certification: Pubkey,
+    pub supplier: Pubkey,
+    pub cert_type: u8,
+    pub issuer: Pubkey,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CertificationRevokedEvent {
+    pub certification: Pubkey,
+    pub supplier: Pubkey,
+    pub cert_type: u8,
+    pub revoked_by: Pubkey,
+    pub timestamp: i64,
+}
+
+// Accounts
+// Certifications are addressed by ["certification", supplier, cert_type]
+pub const CERTIFICATION_SEED: &[u8] = b"certification";
+
+#[account]
+#[derive(InitSpace)]
+pub struct Certification {
+    pub supplier: Pubkey,
+    pub cert_type: u8,
+    pub issuer: Pubkey,
+    pub issued_at: i64,
+    pub expires_at: i64,
+    // Hash of the certificate document kept off-chain
+    pub document_hash: [u8; 32],
+    pub revoked: bool,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl Certification {
+    pub fn space() -> usize {
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+
+    pub fn is_valid_at(&self, timestamp: i64) -> bool {
+        !self.revoked && timestamp < self.expires_at
+    }
+}
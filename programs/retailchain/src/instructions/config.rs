@@ -0,0 +1,356 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::SupplyChainError;
+use crate::program::Retailchain;
+
+// Program-wide configuration.
+// A single config PDA holds the admin, pause switches and fee parameters. Every
+// instruction outside this module checks it through `ProgramConfig::ensure_active`,
+// so operators can freeze parts of the program during an incident without a redeploy.
+pub fn initialize_config(
+    ctx: Context<InitializeConfig>,
+    fee_bps: u16
+) -> Result<()> {
+    require!(
+        fee_bps <= MAX_FEE_BPS,
+        SupplyChainError::InvalidFeeBps
+    );
+
+    let config = &mut ctx.accounts.config;
+    config.admin = ctx.accounts.authority.key();
+    config.pending_admin = None;
+    config.paused = false;
+    config.paused_modules = 0;
+    config.fee_bps = fee_bps;
+    config.bump = ctx.bumps.config;
+    config.version = CURRENT_ACCOUNT_VERSION;
+
+    let treasury = &mut ctx.accounts.treasury;
+    treasury.total_collected = 0;
+    treasury.total_withdrawn = 0;
+    treasury.bump = ctx.bumps.treasury;
+    treasury.version = CURRENT_ACCOUNT_VERSION;
+
+    emit!(ConfigInitializedEvent {
+        admin: config.admin,
+        fee_bps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// First step of an admin transfer; passing None withdraws a pending proposal
+pub fn propose_admin(
+    ctx: Context<UpdateConfig>,
+    new_admin: Option<Pubkey>
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.pending_admin = new_admin;
+
+    emit!(AdminTransferProposedEvent {
+        admin: config.admin,
+        pending_admin: new_admin,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Second step of an admin transfer, signed by the proposed admin
+pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let previous_admin = config.admin;
+
+    config.admin = ctx.accounts.pending_admin.key();
+    config.pending_admin = None;
+
+    emit!(AdminTransferredEvent {
+        previous_admin,
+        new_admin: config.admin,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// `paused` freezes every guarded instruction; `paused_modules` freezes only the
+// modules whose MODULE_* bits are set
+pub fn set_pause(
+    ctx: Context<UpdateConfig>,
+    paused: bool,
+    paused_modules: u16
+) -> Result<()> {
+    require!(
+        paused_modules & !MODULE_ALL == 0,
+        SupplyChainError::InvalidData
+    );
+
+    let config = &mut ctx.accounts.config;
+    config.paused = paused;
+    config.paused_modules = paused_modules;
+
+    emit!(PauseUpdatedEvent {
+        paused,
+        paused_modules,
+        updated_by: ctx.accounts.admin.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn set_fee_params(
+    ctx: Context<UpdateConfig>,
+    fee_bps: u16
+) -> Result<()> {
+    require!(
+        fee_bps <= MAX_FEE_BPS,
+        SupplyChainError::InvalidFeeBps
+    );
+
+    let config = &mut ctx.accounts.config;
+    let old_fee_bps = config.fee_bps;
+    config.fee_bps = fee_bps;
+
+    emit!(FeeParamsUpdatedEvent {
+        old_fee_bps,
+        new_fee_bps: fee_bps,
+        updated_by: ctx.accounts.admin.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Fees accumulate as lamports on the treasury PDA; its rent-exempt minimum stays put
+pub fn withdraw_fees(
+    ctx: Context<WithdrawFees>,
+    amount: u64
+) -> Result<()> {
+    let treasury = &mut ctx.accounts.treasury;
+
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(treasury.to_account_info().data_len());
+    let available = treasury.get_lamports().saturating_sub(rent_exempt_minimum);
+    require!(
+        amount > 0 && amount <= available,
+        SupplyChainError::InvalidPaymentAmount
+    );
+
+    treasury.sub_lamports(amount)?;
+    ctx.accounts.recipient.add_lamports(amount)?;
+
+    treasury.total_withdrawn = treasury.total_withdrawn.checked_add(amount)
+        .ok_or(SupplyChainError::ArithmeticError)?;
+
+    emit!(FeesWithdrawnEvent {
+        recipient: ctx.accounts.recipient.key(),
+        amount,
+        withdrawn_by: ctx.accounts.admin.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Account contexts for configuration operations
+// The config can only be created once, by the program's upgrade authority
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = ProgramConfig::space(),
+        seeds = [CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        init,
+        payer = authority,
+        space = Treasury::space(),
+        seeds = [TREASURY_SEED],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key())
+    )]
+    pub program: Program<'info, Retailchain>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key()) @ SupplyChainError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = config.admin == admin.key() @ SupplyChainError::Unauthorized
+    )]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub pending_admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = config.pending_admin == Some(pending_admin.key()) @ SupplyChainError::Unauthorized
+    )]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = config.admin == admin.key() @ SupplyChainError::Unauthorized
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    /// CHECK: any account chosen by the admin can receive the fees
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+}
+
+// Events
+#[event]
+pub struct ConfigInitializedEvent {
+    pub admin: Pubkey,
+    pub fee_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminTransferProposedEvent {
+    pub admin: Pubkey,
+    pub pending_admin: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminTransferredEvent {
+    pub previous_admin: Pubkey,
+    pub new_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PauseUpdatedEvent {
+    pub paused: bool,
+    pub paused_modules: u16,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeParamsUpdatedEvent {
+    pub old_fee_bps: u16,
+    pub new_fee_bps: u16,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeesWithdrawnEvent {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub withdrawn_by: Pubkey,
+    pub timestamp: i64,
+}
+
+// Accounts
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const TREASURY_SEED: &[u8] = b"treasury";
+
+// Upper bound on the protocol fee (10%)
+pub const MAX_FEE_BPS: u16 = 1_000;
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+// Pause bits, one per instruction module
+pub const MODULE_STORES: u16 = 1 << 0;
+pub const MODULE_SUPPLIERS: u16 = 1 << 1;
+pub const MODULE_AGREEMENTS: u16 = 1 << 2;
+pub const MODULE_SHIPMENTS: u16 = 1 << 3;
+pub const MODULE_PACKAGES: u16 = 1 << 4;
+pub const MODULE_IOT: u16 = 1 << 5;
+pub const MODULE_EVENTS: u16 = 1 << 6;
+pub const MODULE_VERIFIERS: u16 = 1 << 7;
+pub const MODULE_ORDERS: u16 = 1 << 8;
+pub const MODULE_ALL: u16 = MODULE_STORES | MODULE_SUPPLIERS | MODULE_AGREEMENTS |
+    MODULE_SHIPMENTS | MODULE_PACKAGES | MODULE_IOT | MODULE_EVENTS | MODULE_VERIFIERS |
+    MODULE_ORDERS;
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProgramConfig {
+    pub admin: Pubkey,
+    pub pending_admin: Option<Pubkey>,
+    pub paused: bool,
+    pub paused_modules: u16,
+    pub fee_bps: u16,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl ProgramConfig {
+    pub fn space() -> usize {
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+
+    // Guard used by `access_control` on every instruction of the given modules
+    pub fn ensure_active(&self, modules: u16) -> Result<()> {
+        require!(
+            !self.paused,
+            SupplyChainError::ProgramPaused
+        );
+        require!(
+            self.paused_modules & modules == 0,
+            SupplyChainError::ModulePaused
+        );
+        Ok(())
+    }
+
+    // Protocol fee charged on an escrow release, rounded down
+    pub fn fee_for(&self, amount: u64) -> Result<u64> {
+        let fee = (amount as u128)
+            .checked_mul(self.fee_bps as u128)
+            .ok_or(SupplyChainError::ArithmeticError)?
+            / BPS_DENOMINATOR as u128;
+        Ok(fee as u64)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    pub total_collected: u64,
+    pub total_withdrawn: u64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl Treasury {
+    pub fn space() -> usize {
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+}
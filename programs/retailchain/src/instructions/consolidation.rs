@@ -0,0 +1,394 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::SupplyChainError;
+use super::{validate_string_length, has_supplier_role};
+use super::shipment::{ShipmentCarrier, ShipmentCreatedEvent, ShipmentStatusUpdatedEvent, TrackingIdRecord, TRACKING_ID_SEED};
+use super::config::{ProgramConfig, CONFIG_SEED};
+use super::supplier_member::{SupplierMember, SUPPLIER_ROLE_SHIPPING};
+
+// Shipment splitting and consolidation.
+// A split parent hands products to child shipments and keeps its own status while it
+// still carries products; once its product list is empty, and for consolidated
+// masters, the parent's status is rolled up from the children by `sync_shipment_status`.
+pub fn split_shipment(
+    ctx: Context<SplitShipment>,
+    tracking_id: String,
+    products: Vec<Pubkey>
+) -> Result<()> {
+    let parent = &mut ctx.accounts.parent_shipment;
+    let child = &mut ctx.accounts.child_shipment;
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    // Shipments can be split before pickup or at a cross-dock while in transit
+    require!(
+        parent.status == ShipmentStatus::Created as u8 ||
+        parent.status == ShipmentStatus::InTransit as u8,
+        SupplyChainError::InvalidStatusTransition
+    );
+    
+    require!(
+        !tracking_id.is_empty(),
+        SupplyChainError::InvalidData
+    );
+    validate_string_length(&tracking_id, MAX_TRACKING_ID_LEN)?;
+    
+    require!(
+        !products.is_empty(),
+        SupplyChainError::MissingRequiredField
+    );
+    
+    require!(
+        parent.child_shipments.len() < MAX_CHILD_SHIPMENTS,
+        SupplyChainError::ResourceLimitExceeded
+    );
+    
+    // Move the products out of the parent
+    for product in products.iter() {
+        let position = parent.products.iter()
+            .position(|parent_product| parent_product == product)
+            .ok_or(SupplyChainError::EntityNotFound)?;
+        parent.products.remove(position);
+    }
+    
+    // The child inherits the parent's route endpoints, ETA and agreement link
+    child.initialize(
+        tracking_id,
+        parent.supplier,
+        parent.destination,
+        parent.origin_location.clone(),
+        parent.destination_location.clone(),
+        parent.estimated_arrival,
+        products,
+        current_time,
+    );
+    child.original_eta = parent.committed_eta();
+    child.bump = ctx.bumps.child_shipment;
+    child.version = CURRENT_ACCOUNT_VERSION;
+    ctx.accounts.tracking_id_record.claim(
+        &child.tracking_id,
+        child.key(),
+        child.supplier,
+        ctx.bumps.tracking_id_record,
+        current_time,
+    );
+    child.status = parent.status;
+    child.agreement = parent.agreement;
+    child.set_purchase_order(parent.purchase_order());
+    child.parent_shipment = Some(parent.key());
+    
+    parent.child_shipments.push(child.key());
+    
+    emit!(ShipmentCreatedEvent {
+        shipment: child.key(),
+        supplier: child.supplier,
+        destination: child.destination,
+        tracking_id: child.tracking_id.clone(),
+        estimated_arrival: child.estimated_arrival,
+        timestamp: current_time,
+    });
+    
+    emit!(ShipmentSplitEvent {
+        parent_shipment: parent.key(),
+        child_shipment: child.key(),
+        products: child.products.clone(),
+        split_by: ctx.accounts.authority.key(),
+        timestamp: current_time,
+    });
+    
+    Ok(())
+}
+
+// Child shipments to consolidate are passed as remaining accounts, each writable
+// child followed by the carrier assignment that hands it to the consolidating carrier
+pub fn consolidate_shipments<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ConsolidateShipments<'info>>,
+    tracking_id: String,
+    origin_location: String
+) -> Result<()> {
+    let master = &mut ctx.accounts.master_shipment;
+    let master_key = master.key();
+    let store_key = ctx.accounts.store.key();
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    require!(
+        !tracking_id.is_empty(),
+        SupplyChainError::InvalidData
+    );
+    validate_string_length(&tracking_id, MAX_TRACKING_ID_LEN)?;
+    validate_string_length(&origin_location, MAX_LOCATION_LEN)?;
+    
+    require!(
+        !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len() % 2 == 0,
+        SupplyChainError::MissingRequiredField
+    );
+    
+    require!(
+        ctx.remaining_accounts.len() / 2 <= MAX_CHILD_SHIPMENTS,
+        SupplyChainError::ResourceLimitExceeded
+    );
+    
+    let mut children: Vec<Pubkey> = Vec::new();
+    let mut statuses: Vec<u8> = Vec::new();
+    let mut common_supplier: Option<Pubkey> = None;
+    let mut mixed_suppliers = false;
+    let mut estimated_arrival = 0;
+    let mut destination_location = String::new();
+    
+    for pair in ctx.remaining_accounts.chunks(2) {
+        let child_info = &pair[0];
+        require!(
+            child_info.is_writable && !children.contains(child_info.key),
+            SupplyChainError::InvalidData
+        );
+        
+        let mut child: Account<'info, ShipmentRecord> = Account::try_from(child_info)?;
+        
+        // Only shipments the suppliers have handed to this carrier can be grouped
+        let carrier_assignment: Account<'info, ShipmentCarrier> = Account::try_from(&pair[1])?;
+        require!(
+            carrier_assignment.assigns(child.key(), ctx.accounts.authority.key()),
+            SupplyChainError::Unauthorized
+        );
+        
+        // All children must be heading to the same store and not already grouped
+        require!(
+            child.destination == store_key,
+            SupplyChainError::ShipmentNotRelated
+        );
+        
+        require!(
+            child.parent_shipment.is_none(),
+            SupplyChainError::InvalidRelationship
+        );
+        
+        require!(
+            child.status == ShipmentStatus::Created as u8 ||
+            child.status == ShipmentStatus::InTransit as u8,
+            SupplyChainError::InvalidStatusTransition
+        );
+        
+        match common_supplier {
+            None => common_supplier = Some(child.supplier),
+            Some(supplier) if supplier != child.supplier => mixed_suppliers = true,
+            _ => {},
+        }
+        
+        estimated_arrival = estimated_arrival.max(child.estimated_arrival);
+        destination_location = child.destination_location.clone();
+        children.push(child.key());
+        statuses.push(child.status);
+        
+        child.parent_shipment = Some(master_key);
+        child.exit(&crate::ID)?;
+    }
+    
+    // A master spanning several suppliers belongs to no single supplier
+    let supplier = if mixed_suppliers {
+        Pubkey::default()
+    } else {
+        common_supplier.unwrap_or_default()
+    };
+    
+    master.initialize(
+        tracking_id,
+        supplier,
+        store_key,
+        origin_location,
+        destination_location,
+        estimated_arrival,
+        Vec::new(),
+        current_time,
+    );
+    master.bump = ctx.bumps.master_shipment;
+    master.version = CURRENT_ACCOUNT_VERSION;
+    ctx.accounts.tracking_id_record.claim(
+        &master.tracking_id,
+        master_key,
+        supplier,
+        ctx.bumps.tracking_id_record,
+        current_time,
+    );
+    master.is_master = true;
+    master.status = rollup_shipment_status(&statuses);
+    master.child_shipments = children.clone();
+    
+    emit!(ShipmentCreatedEvent {
+        shipment: master_key,
+        supplier,
+        destination: store_key,
+        tracking_id: master.tracking_id.clone(),
+        estimated_arrival,
+        timestamp: current_time,
+    });
+    
+    emit!(ShipmentsConsolidatedEvent {
+        master_shipment: master_key,
+        child_shipments: children,
+        consolidated_by: ctx.accounts.authority.key(),
+        timestamp: current_time,
+    });
+    
+    Ok(())
+}
+
+// Permissionless crank: children are passed as remaining accounts in the order
+// they are listed on the parent
+pub fn sync_shipment_status<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SyncShipmentStatus<'info>>
+) -> Result<()> {
+    let shipment = &mut ctx.accounts.shipment;
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    // A split parent only rolls up once all of its products were handed to children;
+    // until then its own status is updated directly
+    require!(
+        shipment.is_rolled_up(),
+        SupplyChainError::InvalidRelationship
+    );
+    
+    require!(
+        ctx.remaining_accounts.len() == shipment.child_shipments.len(),
+        SupplyChainError::MissingRequiredField
+    );
+    
+    let mut statuses: Vec<u8> = Vec::new();
+    for (child_info, expected) in ctx.remaining_accounts.iter().zip(shipment.child_shipments.iter()) {
+        require!(
+            child_info.key == expected,
+            SupplyChainError::ShipmentNotRelated
+        );
+        
+        let child: Account<'info, ShipmentRecord> = Account::try_from(child_info)?;
+        statuses.push(child.status);
+    }
+    
+    let old_status = shipment.status;
+    let new_status = rollup_shipment_status(&statuses);
+    
+    if new_status != old_status {
+        shipment.status = new_status;
+        
+        emit!(ShipmentStatusUpdatedEvent {
+            shipment: shipment.key(),
+            old_status,
+            new_status,
+            updated_by: ctx.accounts.cranker.key(),
+            timestamp: current_time,
+        });
+    }
+    
+    Ok(())
+}
+
+// Account contexts for split and consolidation operations
+#[derive(Accounts)]
+#[instruction(tracking_id: String)]
+pub struct SplitShipment<'info> {
+    // Longer values cannot be used as PDA seeds
+    #[account(
+        mut,
+        constraint = tracking_id.len() <= MAX_TRACKING_ID_LEN @ SupplyChainError::StringTooLong
+    )]
+    pub authority: Signer<'info>,
+    // Either the supplier or a credentialed carrier can split a shipment
+    #[account(
+        mut,
+        constraint = (parent_shipment.supplier == supplier.key() && has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_SHIPPING)) ||
+                    (authority_credentials.is_some() &&
+                     authority_credentials.as_ref().unwrap().authority == authority.key() &&
+                     authority_credentials.as_ref().unwrap().is_verifier)
+    )]
+    pub parent_shipment: Account<'info, ShipmentRecord>,
+    pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    // Global tracking ID registry entry; an already claimed ID fails here
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = TrackingIdRecord::space(),
+        seeds = [TRACKING_ID_SEED, tracking_id.as_bytes()],
+        bump,
+        constraint = !tracking_id_record.is_claimed() @ SupplyChainError::DuplicateEntry
+    )]
+    pub tracking_id_record: Account<'info, TrackingIdRecord>,
+    #[account(
+        init,
+        payer = authority,
+        space = ShipmentRecord::space(),
+        seeds = [SHIPMENT_SEED, parent_shipment.supplier.as_ref(), tracking_id.as_bytes()],
+        bump
+    )]
+    pub child_shipment: Account<'info, ShipmentRecord>,
+    pub authority_credentials: Option<Account<'info, VerifierCredential>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+// Consolidation happens at a cross-dock, so only credentialed carriers can do it, and
+// only for shipments assigned to them. The master is addressed by the consolidating
+// carrier rather than a supplier.
+#[derive(Accounts)]
+#[instruction(tracking_id: String)]
+pub struct ConsolidateShipments<'info> {
+    // Longer values cannot be used as PDA seeds
+    #[account(
+        mut,
+        constraint = tracking_id.len() <= MAX_TRACKING_ID_LEN @ SupplyChainError::StringTooLong
+    )]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = authority_credentials.authority == authority.key(),
+        constraint = authority_credentials.is_verifier
+    )]
+    pub authority_credentials: Account<'info, VerifierCredential>,
+    pub store: Account<'info, Store>,
+    // Global tracking ID registry entry; an already claimed ID fails here
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = TrackingIdRecord::space(),
+        seeds = [TRACKING_ID_SEED, tracking_id.as_bytes()],
+        bump,
+        constraint = !tracking_id_record.is_claimed() @ SupplyChainError::DuplicateEntry
+    )]
+    pub tracking_id_record: Account<'info, TrackingIdRecord>,
+    #[account(
+        init,
+        payer = authority,
+        space = ShipmentRecord::space(),
+        seeds = [SHIPMENT_SEED, authority.key().as_ref(), tracking_id.as_bytes()],
+        bump
+    )]
+    pub master_shipment: Account<'info, ShipmentRecord>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SyncShipmentStatus<'info> {
+    pub cranker: Signer<'info>,
+    #[account(mut)]
+    pub shipment: Account<'info, ShipmentRecord>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// Event definitions
+#[event]
+pub struct ShipmentSplitEvent {
+    pub parent_shipment: Pubkey,
+    pub child_shipment: Pubkey,
+    pub products: Vec<Pubkey>,
+    pub split_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ShipmentsConsolidatedEvent {
+    pub master_shipment: Pubkey,
+    pub child_shipments: Vec<Pubkey>,
+    pub consolidated_by: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,318 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar::instructions::{
+    self as instructions_sysvar,
+    load_current_index_checked,
+    load_instruction_at_checked,
+};
+use crate::state::*;
+use crate::errors::SupplyChainError;
+use super::has_store_role;
+use super::shipment::ShipmentStatusUpdatedEvent;
+use super::store_member::{StoreMember, STORE_ROLE_RECEIVING};
+use super::config::{ProgramConfig, CONFIG_SEED};
+
+// Signed proof of delivery.
+// The recipient (store owner, a receiving staff member, or the shipment's designated
+// recipient, e.g. a dock worker's handheld key) signs a `DeliveryAttestation` off-chain. The submitting
+// transaction carries an Ed25519 program instruction verifying that signature
+// directly before `submit_proof_of_delivery`, which checks it via instruction
+// introspection.
+pub fn designate_recipient(
+    ctx: Context<DesignateRecipient>,
+    recipient: Option<Pubkey>
+) -> Result<()> {
+    let shipment = &mut ctx.accounts.shipment;
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    // The recipient can only change while the shipment is on its way
+    require!(
+        shipment.status == ShipmentStatus::Created as u8 ||
+        shipment.status == ShipmentStatus::InTransit as u8,
+        SupplyChainError::InvalidStatusTransition
+    );
+    
+    shipment.designated_recipient = recipient;
+    
+    emit!(RecipientDesignatedEvent {
+        shipment: shipment.key(),
+        recipient,
+        designated_by: ctx.accounts.authority.key(),
+        timestamp: current_time,
+    });
+    
+    Ok(())
+}
+
+pub fn submit_proof_of_delivery(
+    ctx: Context<SubmitProofOfDelivery>,
+    recipient: Pubkey,
+    received_quantities: Vec<u64>,
+    signed_at: i64,
+    photo_hash: Option<[u8; 32]>
+) -> Result<()> {
+    let shipment = &mut ctx.accounts.shipment;
+    let proof = &mut ctx.accounts.proof_of_delivery;
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    // Receipt can be attested on arrival or for an already delivered, contested shipment
+    require!(
+        shipment.status == ShipmentStatus::InTransit as u8 ||
+        shipment.status == ShipmentStatus::Delivered as u8,
+        SupplyChainError::InvalidStatusTransition
+    );
+    
+    require!(
+        !shipment.is_rolled_up(),
+        SupplyChainError::ShipmentStatusRolledUp
+    );
+    
+    // Only the store owner, receiving staff or the designated recipient can attest receipt
+    require!(
+        has_store_role(&ctx.accounts.store, &ctx.accounts.recipient_membership, recipient, STORE_ROLE_RECEIVING) ||
+        shipment.designated_recipient == Some(recipient),
+        SupplyChainError::Unauthorized
+    );
+    
+    // One received quantity per product on the shipment
+    require!(
+        received_quantities.len() == shipment.products.len(),
+        SupplyChainError::InvalidData
+    );
+    
+    require!(
+        signed_at <= current_time && signed_at >= shipment.created_at,
+        SupplyChainError::InvalidTimestamp
+    );
+    
+    // Rebuild the signed message and check it against the Ed25519 instruction
+    let attestation = DeliveryAttestation {
+        shipment: shipment.key(),
+        received_quantities: received_quantities.clone(),
+        timestamp: signed_at,
+        photo_hash,
+    };
+    let message = attestation.try_to_vec()
+        .map_err(|_| SupplyChainError::SerializationError)?;
+    
+    let instructions_info = ctx.accounts.instructions_sysvar.to_account_info();
+    let current_index = load_current_index_checked(&instructions_info)? as usize;
+    require!(
+        current_index > 0,
+        SupplyChainError::InvalidSignature
+    );
+    let ed25519_ix = load_instruction_at_checked(current_index - 1, &instructions_info)?;
+    let signature = verify_ed25519_instruction(&ed25519_ix, &recipient, &message)?;
+    
+    // Record the proof of delivery
+    proof.shipment = shipment.key();
+    proof.recipient = recipient;
+    proof.submitted_by = ctx.accounts.authority.key();
+    proof.received_quantities = received_quantities;
+    proof.signed_at = signed_at;
+    proof.photo_hash = photo_hash;
+    proof.signature = signature;
+    proof.recorded_at = current_time;
+    proof.bump = ctx.bumps.proof_of_delivery;
+    proof.version = CURRENT_ACCOUNT_VERSION;
+    proof.stock_received = false;
+    
+    emit!(ProofOfDeliveryRecordedEvent {
+        shipment: shipment.key(),
+        proof_of_delivery: proof.key(),
+        recipient,
+        signed_at,
+        timestamp: current_time,
+    });
+    
+    // A signed receipt marks an in-transit shipment as delivered
+    if shipment.status == ShipmentStatus::InTransit as u8 {
+        shipment.status = ShipmentStatus::Delivered as u8;
+        
+        if shipment.counts_toward_supplier_metrics() {
+            ctx.accounts.supplier.record_delivery(signed_at <= shipment.committed_eta())?;
+        }
+        
+        emit!(ShipmentStatusUpdatedEvent {
+            shipment: shipment.key(),
+            old_status: ShipmentStatus::InTransit as u8,
+            new_status: ShipmentStatus::Delivered as u8,
+            updated_by: recipient,
+            timestamp: current_time,
+        });
+    }
+    
+    Ok(())
+}
+
+// Layout of the Ed25519 program instruction data: a 2-byte header followed by one
+// 14-byte offsets record per signature, then the signature, key and message bytes
+const ED25519_HEADER_LEN: usize = 2;
+const ED25519_OFFSETS_LEN: usize = 14;
+const ED25519_SIGNATURE_LEN: usize = 64;
+const ED25519_PUBKEY_LEN: usize = 32;
+// Instruction index meaning "the data lives in the Ed25519 instruction itself"
+const ED25519_CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+// Checks that `ix` is an Ed25519 program instruction verifying exactly one
+// signature by `signer` over `message`, and returns that signature
+fn verify_ed25519_instruction(
+    ix: &Instruction,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<[u8; 64]> {
+    require!(
+        ix.program_id == ed25519_program::ID && ix.accounts.is_empty(),
+        SupplyChainError::InvalidSignature
+    );
+    
+    let data = &ix.data;
+    require!(
+        data.len() >= ED25519_HEADER_LEN + ED25519_OFFSETS_LEN && data[0] == 1,
+        SupplyChainError::InvalidSignature
+    );
+    
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let offsets = ED25519_HEADER_LEN;
+    let signature_offset = read_u16(offsets) as usize;
+    let signature_ix_index = read_u16(offsets + 2);
+    let pubkey_offset = read_u16(offsets + 4) as usize;
+    let pubkey_ix_index = read_u16(offsets + 6);
+    let message_offset = read_u16(offsets + 8) as usize;
+    let message_size = read_u16(offsets + 10) as usize;
+    let message_ix_index = read_u16(offsets + 12);
+    
+    // Signature, key and message must all be embedded in the Ed25519 instruction
+    require!(
+        signature_ix_index == ED25519_CURRENT_INSTRUCTION &&
+        pubkey_ix_index == ED25519_CURRENT_INSTRUCTION &&
+        message_ix_index == ED25519_CURRENT_INSTRUCTION,
+        SupplyChainError::InvalidSignature
+    );
+    
+    let signed_pubkey = data.get(pubkey_offset..pubkey_offset + ED25519_PUBKEY_LEN)
+        .ok_or(SupplyChainError::InvalidSignature)?;
+    let signed_message = data.get(message_offset..message_offset + message_size)
+        .ok_or(SupplyChainError::InvalidSignature)?;
+    let signature = data.get(signature_offset..signature_offset + ED25519_SIGNATURE_LEN)
+        .ok_or(SupplyChainError::InvalidSignature)?;
+    
+    require!(
+        signed_pubkey == signer.as_ref() && signed_message == message,
+        SupplyChainError::InvalidSignature
+    );
+    
+    let mut signature_bytes = [0u8; ED25519_SIGNATURE_LEN];
+    signature_bytes.copy_from_slice(signature);
+    Ok(signature_bytes)
+}
+
+// Account contexts for proof of delivery operations
+#[derive(Accounts)]
+pub struct DesignateRecipient<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_store_role(&store, &store_member, authority.key(), STORE_ROLE_RECEIVING)
+    )]
+    pub store: Account<'info, Store>,
+    pub store_member: Option<Account<'info, StoreMember>>,
+    #[account(
+        mut,
+        constraint = shipment.destination == store.key()
+    )]
+    pub shipment: Account<'info, ShipmentRecord>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// Anyone (driver, dock handheld, relayer) can submit the recipient's signed receipt
+#[derive(Accounts)]
+pub struct SubmitProofOfDelivery<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub store: Account<'info, Store>,
+    // The recipient's store membership when a staff member signed the attestation
+    pub recipient_membership: Option<Account<'info, StoreMember>>,
+    #[account(
+        mut,
+        constraint = shipment.destination == store.key()
+    )]
+    pub shipment: Account<'info, ShipmentRecord>,
+    #[account(
+        mut,
+        constraint = shipment.supplier == supplier.key()
+    )]
+    pub supplier: Account<'info, Supplier>,
+    #[account(
+        init,
+        payer = authority,
+        space = ProofOfDelivery::space(),
+        seeds = [PROOF_OF_DELIVERY_SEED, shipment.key().as_ref()],
+        bump
+    )]
+    pub proof_of_delivery: Account<'info, ProofOfDelivery>,
+    /// CHECK: address is checked against the instructions sysvar id
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+// Event definitions
+#[event]
+pub struct RecipientDesignatedEvent {
+    pub shipment: Pubkey,
+    pub recipient: Option<Pubkey>,
+    pub designated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProofOfDeliveryRecordedEvent {
+    pub shipment: Pubkey,
+    pub proof_of_delivery: Pubkey,
+    pub recipient: Pubkey,
+    pub signed_at: i64,
+    pub timestamp: i64,
+}
+
+// Message signed by the recipient: the Borsh serialization of this struct.
+// The photo hash is part of the signed message so it cannot be swapped afterwards.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DeliveryAttestation {
+    pub shipment: Pubkey,
+    pub received_quantities: Vec<u64>,
+    pub timestamp: i64,
+    pub photo_hash: Option<[u8; 32]>,
+}
+
+// Proof of delivery account structure, one per shipment
+pub const PROOF_OF_DELIVERY_SEED: &[u8] = b"proof_of_delivery";
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProofOfDelivery {
+    pub shipment: Pubkey,
+    pub recipient: Pubkey,
+    pub submitted_by: Pubkey,
+    #[max_len(MAX_SHIPMENT_PRODUCTS)]
+    pub received_quantities: Vec<u64>,
+    pub signed_at: i64,
+    pub photo_hash: Option<[u8; 32]>,
+    pub signature: [u8; 64],
+    pub recorded_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    // Set once the received quantities have been added to store inventory
+    pub stock_received: bool,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES - 1],
+}
+
+impl ProofOfDelivery {
+    pub fn space() -> usize {
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+}
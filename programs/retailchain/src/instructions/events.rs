@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::SupplyChainError;
+use super::validate_string_length;
+use super::config::{ProgramConfig, CONFIG_SEED};
+
+// Supply chain event log.
+// Each event is its own account so the audit trail cannot be rewritten.
+pub fn record_supply_chain_event(
+    ctx: Context<RecordEvent>,
+    event_type: u8,
+    location: String,
+    timestamp: i64,
+    metadata: String
+) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+
+    require!(
+        event_type <= EventType::Payment as u8,
+        SupplyChainError::InvalidData
+    );
+    validate_string_length(&location, MAX_LOCATION_LEN)?;
+    validate_string_length(&metadata, MAX_METADATA_LEN)?;
+
+    require!(
+        timestamp <= current_time,
+        SupplyChainError::InvalidTimestamp
+    );
+
+    let event = &mut ctx.accounts.event;
+    event.event_type = event_type;
+    event.recorder = ctx.accounts.recorder.key();
+    event.related_entity = ctx.accounts.related_entity.key();
+    event.location = location;
+    event.timestamp = timestamp;
+    event.metadata = metadata;
+    event.created_at = current_time;
+    event.version = CURRENT_ACCOUNT_VERSION;
+
+    emit!(SupplyChainEventRecordedEvent {
+        event: event.key(),
+        event_type,
+        recorder: event.recorder,
+        related_entity: event.related_entity,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+// Account contexts for event logging
+#[derive(Accounts)]
+pub struct RecordEvent<'info> {
+    #[account(mut)]
+    pub recorder: Signer<'info>,
+    /// CHECK: the entity the event refers to is only recorded by key
+    pub related_entity: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = recorder,
+        space = SupplyChainEvent::space()
+    )]
+    pub event: Account<'info, SupplyChainEvent>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+// Events
+#[event]
+pub struct SupplyChainEventRecordedEvent {
+    pub event: Pubkey,
+    pub event_type: u8,
+    pub recorder: Pubkey,
+    pub related_entity: Pubkey,
+    pub timestamp: i64,
+}
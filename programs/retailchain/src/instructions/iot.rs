@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::SupplyChainError;
+use super::validate_string_length;
+use super::config::{ProgramConfig, CONFIG_SEED};
+
+// IoT sensor readings.
+// Devices record readings against a shipment under their own key; a verifier
+// later marks individual readings as verified.
+pub fn add_iot_data(
+    ctx: Context<AddIoTData>,
+    data_type: u8,
+    value: String,
+    timestamp: i64
+) -> Result<()> {
+    require!(
+        data_type <= IoTDataType::LightExposure as u8,
+        SupplyChainError::InvalidData
+    );
+    validate_string_length(&value, MAX_IOT_VALUE_LEN)?;
+
+    require!(
+        timestamp <= Clock::get()?.unix_timestamp,
+        SupplyChainError::InvalidTimestamp
+    );
+
+    let iot_data = &mut ctx.accounts.iot_data;
+    iot_data.shipment = ctx.accounts.shipment.key();
+    iot_data.data_type = data_type;
+    iot_data.value = value;
+    iot_data.timestamp = timestamp;
+    iot_data.recorder = ctx.accounts.recorder.key();
+    iot_data.is_verified = false;
+    iot_data.version = CURRENT_ACCOUNT_VERSION;
+
+    emit!(IoTDataRecordedEvent {
+        iot_data: iot_data.key(),
+        shipment: iot_data.shipment,
+        data_type,
+        recorder: iot_data.recorder,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn verify_iot_data(
+    ctx: Context<VerifyIoTData>
+) -> Result<()> {
+    let iot_data = &mut ctx.accounts.iot_data;
+
+    require!(
+        !iot_data.is_verified,
+        SupplyChainError::InvalidData
+    );
+
+    iot_data.is_verified = true;
+
+    emit!(IoTDataVerifiedEvent {
+        iot_data: iot_data.key(),
+        shipment: iot_data.shipment,
+        verified_by: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Account contexts for IoT operations
+#[derive(Accounts)]
+pub struct AddIoTData<'info> {
+    #[account(mut)]
+    pub recorder: Signer<'info>,
+    pub shipment: Account<'info, ShipmentRecord>,
+    #[account(
+        init,
+        payer = recorder,
+        space = IoTDataRecord::space()
+    )]
+    pub iot_data: Account<'info, IoTDataRecord>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyIoTData<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = authority_credentials.authority == authority.key(),
+        constraint = authority_credentials.is_verifier @ SupplyChainError::UnauthorizedVerifier
+    )]
+    pub authority_credentials: Account<'info, VerifierCredential>,
+    #[account(mut)]
+    pub iot_data: Account<'info, IoTDataRecord>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// Events
+#[event]
+pub struct IoTDataRecordedEvent {
+    pub iot_data: Pubkey,
+    pub shipment: Pubkey,
+    pub data_type: u8,
+    pub recorder: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct IoTDataVerifiedEvent {
+    pub iot_data: Pubkey,
+    pub shipment: Pubkey,
+    pub verified_by: Pubkey,
+    pub timestamp: i64,
+}
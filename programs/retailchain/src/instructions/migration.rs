@@ -0,0 +1,1169 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_lang::Discriminator;
+use crate::state::*;
+use crate::errors::SupplyChainError;
+use super::supplier::{SupplierProduct, SupplierReview, SupplierReverification};
+use super::agreement::AgreementDispute;
+use super::shipment::{TrackingIdRecord, ShipmentException, EtaRevision, ShipmentLocationPage, ShipmentCarrier};
+use super::delivery::ProofOfDelivery;
+use super::package::Package;
+use super::config::{ProgramConfig, Treasury};
+use super::store_member::StoreMember;
+use super::supplier_member::SupplierMember;
+use super::approval::PendingApproval;
+use super::certification::Certification;
+use super::pricing::{PriceSchedule, StorePriceOverride};
+use super::purchase_order::PurchaseOrder;
+use super::product_mapping::ProductMapping;
+
+// In-place account migration.
+// Accounts written before versioning (v0) still have the size and layout the program
+// was first deployed with; see the `v0` module. They are decoded from that layout and
+// upgrade steps are then applied one version at a time up to `CURRENT_ACCOUNT_VERSION`.
+pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+    let account = ctx.accounts.account.to_account_info();
+    let payer = ctx.accounts.payer.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+
+    let discriminator: [u8; 8] = {
+        let data = account.try_borrow_data()?;
+        require!(data.len() >= 8, SupplyChainError::SerializationError);
+        data[..8].try_into().unwrap()
+    };
+
+    let from_version = if discriminator == Store::DISCRIMINATOR {
+        migrate::<Store>(&account, &payer, &system_program, Store::space())?
+    } else if discriminator == Product::DISCRIMINATOR {
+        migrate::<Product>(&account, &payer, &system_program, Product::space())?
+    } else if discriminator == Supplier::DISCRIMINATOR {
+        migrate::<Supplier>(&account, &payer, &system_program, Supplier::space())?
+    } else if discriminator == SupplierProduct::DISCRIMINATOR {
+        migrate::<SupplierProduct>(&account, &payer, &system_program, SupplierProduct::space())?
+    } else if discriminator == VerifierCredential::DISCRIMINATOR {
+        migrate::<VerifierCredential>(&account, &payer, &system_program, VerifierCredential::space())?
+    } else if discriminator == SupplyAgreement::DISCRIMINATOR {
+        migrate::<SupplyAgreement>(&account, &payer, &system_program, SupplyAgreement::space())?
+    } else if discriminator == AgreementDispute::DISCRIMINATOR {
+        migrate::<AgreementDispute>(&account, &payer, &system_program, AgreementDispute::space())?
+    } else if discriminator == ShipmentRecord::DISCRIMINATOR {
+        migrate::<ShipmentRecord>(&account, &payer, &system_program, ShipmentRecord::space())?
+    } else if discriminator == TrackingIdRecord::DISCRIMINATOR {
+        migrate::<TrackingIdRecord>(&account, &payer, &system_program, TrackingIdRecord::space())?
+    } else if discriminator == ShipmentException::DISCRIMINATOR {
+        migrate::<ShipmentException>(&account, &payer, &system_program, ShipmentException::space())?
+    } else if discriminator == EtaRevision::DISCRIMINATOR {
+        migrate::<EtaRevision>(&account, &payer, &system_program, EtaRevision::space())?
+    } else if discriminator == ShipmentLocationPage::DISCRIMINATOR {
+        migrate::<ShipmentLocationPage>(&account, &payer, &system_program, ShipmentLocationPage::space())?
+    } else if discriminator == ShipmentCarrier::DISCRIMINATOR {
+        migrate::<ShipmentCarrier>(&account, &payer, &system_program, ShipmentCarrier::space())?
+    } else if discriminator == ProofOfDelivery::DISCRIMINATOR {
+        migrate::<ProofOfDelivery>(&account, &payer, &system_program, ProofOfDelivery::space())?
+    } else if discriminator == Package::DISCRIMINATOR {
+        migrate::<Package>(&account, &payer, &system_program, Package::space())?
+    } else if discriminator == SupplyChainEvent::DISCRIMINATOR {
+        migrate::<SupplyChainEvent>(&account, &payer, &system_program, SupplyChainEvent::space())?
+    } else if discriminator == IoTDataRecord::DISCRIMINATOR {
+        migrate::<IoTDataRecord>(&account, &payer, &system_program, IoTDataRecord::space())?
+    } else if discriminator == ProgramConfig::DISCRIMINATOR {
+        migrate::<ProgramConfig>(&account, &payer, &system_program, ProgramConfig::space())?
+    } else if discriminator == Treasury::DISCRIMINATOR {
+        migrate::<Treasury>(&account, &payer, &system_program, Treasury::space())?
+    } else if discriminator == StoreMember::DISCRIMINATOR {
+        migrate::<StoreMember>(&account, &payer, &system_program, StoreMember::space())?
+    } else if discriminator == SupplierMember::DISCRIMINATOR {
+        migrate::<SupplierMember>(&account, &payer, &system_program, SupplierMember::space())?
+    } else if discriminator == PendingApproval::DISCRIMINATOR {
+        migrate::<PendingApproval>(&account, &payer, &system_program, PendingApproval::space())?
+    } else if discriminator == SupplierReview::DISCRIMINATOR {
+        migrate::<SupplierReview>(&account, &payer, &system_program, SupplierReview::space())?
+    } else if discriminator == Certification::DISCRIMINATOR {
+        migrate::<Certification>(&account, &payer, &system_program, Certification::space())?
+    } else if discriminator == SupplierReverification::DISCRIMINATOR {
+        migrate::<SupplierReverification>(&account, &payer, &system_program, SupplierReverification::space())?
+    } else if discriminator == PriceSchedule::DISCRIMINATOR {
+        migrate::<PriceSchedule>(&account, &payer, &system_program, PriceSchedule::space())?
+    } else if discriminator == StorePriceOverride::DISCRIMINATOR {
+        migrate::<StorePriceOverride>(&account, &payer, &system_program, StorePriceOverride::space())?
+    } else if discriminator == PurchaseOrder::DISCRIMINATOR {
+        migrate::<PurchaseOrder>(&account, &payer, &system_program, PurchaseOrder::space())?
+    } else if discriminator == ProductMapping::DISCRIMINATOR {
+        migrate::<ProductMapping>(&account, &payer, &system_program, ProductMapping::space())?
+    } else {
+        return err!(SupplyChainError::InvalidData);
+    };
+
+    emit!(AccountMigratedEvent {
+        account: account.key(),
+        from_version,
+        to_version: CURRENT_ACCOUNT_VERSION,
+        migrated_by: payer.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Upgrades one account in place, growing it to `space` and topping up its rent
+// from the payer. Returns the version the account was upgraded from.
+fn migrate<'info, T>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+) -> Result<u8>
+where
+    T: AccountSerialize + VersionedAccount,
+{
+    let (from_version, upgraded) = {
+        let data = account.try_borrow_data()?;
+        upgrade_account_data::<T>(&data[8..], space - 8)?
+    };
+
+    if account.data_len() < space {
+        let required = Rent::get()?.minimum_balance(space);
+        let top_up = required.saturating_sub(account.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    Transfer {
+                        from: payer.clone(),
+                        to: account.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        account.realloc(space, true)?;
+    }
+
+    // Clear bytes left behind by earlier writes before laying out the new version
+    let mut data = account.try_borrow_mut_data()?;
+    data[8..].fill(0);
+    let mut writer: &mut [u8] = &mut data;
+    upgraded.try_serialize(&mut writer)?;
+
+    Ok(from_version)
+}
+
+/// Decodes an account body (without discriminator) stored at any supported layout
+/// version and upgrades it to the current layout. `body_len` is the current body size.
+pub fn upgrade_account_data<T: VersionedAccount>(body: &[u8], body_len: usize) -> Result<(u8, T)> {
+    let mut account = match T::decode_v0(body, body_len) {
+        Some(account) => account?,
+        None => {
+            let mut padded = body.to_vec();
+            if padded.len() < body_len {
+                padded.resize(body_len, 0);
+            }
+
+            let account = T::deserialize(&mut padded.as_slice())
+                .map_err(|_| error!(SupplyChainError::SerializationError))?;
+
+            // Version 0 only exists in the v0 layouts
+            require!(
+                account.version() > 0,
+                SupplyChainError::UnsupportedAccountVersion
+            );
+            account
+        }
+    };
+
+    let from_version = account.version();
+    require!(
+        from_version != CURRENT_ACCOUNT_VERSION,
+        SupplyChainError::AccountAlreadyMigrated
+    );
+    require!(
+        from_version < CURRENT_ACCOUNT_VERSION,
+        SupplyChainError::UnsupportedAccountVersion
+    );
+
+    let mut version = from_version;
+    while version < CURRENT_ACCOUNT_VERSION {
+        apply_upgrade_step(&mut account, version)?;
+        version += 1;
+    }
+
+    Ok((from_version, account))
+}
+
+// Each step upgrades an account from `from_version` to `from_version + 1`
+fn apply_upgrade_step<T: VersionedAccount>(account: &mut T, from_version: u8) -> Result<()> {
+    match from_version {
+        // v0 -> v1: fields added since the v0 layout start out empty, and the version
+        // and reserved bytes are appended after the last field
+        0 => account.reset_trailer(1),
+        _ => return err!(SupplyChainError::UnsupportedAccountVersion),
+    }
+
+    Ok(())
+}
+
+/// Accounts that end with a layout version and reserved bytes. Fields carved out of
+/// the reserved bytes sit between the two and must be cleared by `reset_trailer`.
+pub trait VersionedAccount: AnchorSerialize + AnchorDeserialize {
+    fn version(&self) -> u8;
+
+    // Sets the version and zeroes everything stored after it
+    fn reset_trailer(&mut self, version: u8);
+
+    // Decodes the body if it is in this account type's v0 layout. Types introduced
+    // after versioning have no v0 layout.
+    fn decode_v0(_body: &[u8], _body_len: usize) -> Option<Result<Self>> {
+        None
+    }
+}
+
+// Decodes a body in the v0 layout `L`, recognised by its size, into a blank account of
+// the current layout; `upgrade` copies the v0 fields across
+fn decode_legacy<L, T, F>(body: &[u8], body_len: usize, upgrade: F) -> Option<Result<T>>
+where
+    L: v0::Layout,
+    T: VersionedAccount,
+    F: FnOnce(L, &mut T),
+{
+    if body.len() != L::SPACE - 8 {
+        return None;
+    }
+
+    let decode = || -> Result<T> {
+        let legacy = L::deserialize(&mut &body[..])
+            .map_err(|_| error!(SupplyChainError::SerializationError))?;
+        let mut account = T::deserialize(&mut vec![0u8; body_len].as_slice())
+            .map_err(|_| error!(SupplyChainError::SerializationError))?;
+        upgrade(legacy, &mut account);
+        Ok(account)
+    };
+    Some(decode())
+}
+
+impl VersionedAccount for Store {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.approval_threshold = 0;
+        self.approvals_required = 0;
+        self.reserved.fill(0);
+    }
+
+    fn decode_v0(body: &[u8], body_len: usize) -> Option<Result<Self>> {
+        decode_legacy(body, body_len, |legacy: v0::Store, store: &mut Store| {
+            store.owner = legacy.owner;
+            store.name = legacy.name;
+            store.location = legacy.location;
+            store.total_products = legacy.total_products;
+            store.is_active = legacy.is_active;
+        })
+    }
+}
+
+impl VersionedAccount for Product {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+
+    fn decode_v0(body: &[u8], body_len: usize) -> Option<Result<Self>> {
+        decode_legacy(body, body_len, |legacy: v0::Product, product: &mut Product| {
+            product.store = legacy.store;
+            product.name = legacy.name;
+            product.description = legacy.description;
+            product.price = legacy.price;
+            product.quantity = legacy.quantity;
+            product.created_at = legacy.created_at;
+        })
+    }
+}
+
+impl VersionedAccount for Supplier {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.registered_key = Pubkey::default();
+        self.key_rotation_count = 0;
+        self.rating_sum = 0;
+        self.rating_count = 0;
+        self.rating_average = 0;
+        self.shipments_delivered = 0;
+        self.on_time_deliveries = 0;
+        self.exceptions_raised = 0;
+        self.agreements_completed = 0;
+        self.disputes_lost = 0;
+        self.on_time_bps = 0;
+        self.reserved.fill(0);
+    }
+
+    fn decode_v0(body: &[u8], body_len: usize) -> Option<Result<Self>> {
+        decode_legacy(body, body_len, |legacy: v0::Supplier, supplier: &mut Supplier| {
+            supplier.key = legacy.key;
+            supplier.name = legacy.name;
+            supplier.certification = legacy.certification;
+            supplier.description = legacy.description;
+            supplier.products_supplied = legacy.products_supplied;
+            supplier.verification_status = if legacy.is_verified {
+                VerificationStatus::Verified as u8
+            } else {
+                VerificationStatus::Unverified as u8
+            };
+            supplier.rating = legacy.rating;
+            supplier.created_at = legacy.created_at;
+        })
+    }
+}
+
+impl VersionedAccount for SupplierProduct {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.set_required_certification(None);
+        self.is_discontinued = false;
+        self.reserved_quantity = 0;
+        self.has_price_schedule = false;
+        self.reserved.fill(0);
+    }
+
+    fn decode_v0(body: &[u8], body_len: usize) -> Option<Result<Self>> {
+        decode_legacy(body, body_len, |legacy: v0::SupplierProduct, product: &mut SupplierProduct| {
+            product.supplier = legacy.supplier;
+            product.name = legacy.name;
+            product.description = legacy.description;
+            product.price = legacy.price;
+            product.available_quantity = legacy.available_quantity;
+            product.created_at = legacy.created_at;
+        })
+    }
+}
+
+impl VersionedAccount for VerifierCredential {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+
+    fn decode_v0(body: &[u8], body_len: usize) -> Option<Result<Self>> {
+        decode_legacy(body, body_len, |legacy: v0::VerifierCredential, credential: &mut VerifierCredential| {
+            credential.authority = legacy.authority;
+            credential.is_verifier = legacy.is_verifier;
+            credential.verification_level = legacy.verification_level;
+            credential.organization = legacy.organization;
+        })
+    }
+}
+
+impl VersionedAccount for SupplyAgreement {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.escrow_balance = 0;
+        self.set_required_certification(None);
+        self.reserved.fill(0);
+    }
+
+    fn decode_v0(body: &[u8], body_len: usize) -> Option<Result<Self>> {
+        decode_legacy(body, body_len, |legacy: v0::SupplyAgreement, agreement: &mut SupplyAgreement| {
+            agreement.supplier = legacy.supplier;
+            agreement.store = legacy.store;
+            agreement.terms = legacy.terms;
+            agreement.deadline = legacy.deadline;
+            agreement.payment_amount = legacy.payment_amount;
+            agreement.status = legacy.status;
+            agreement.created_at = legacy.created_at;
+            agreement.products = legacy.products;
+        })
+    }
+}
+
+impl VersionedAccount for AgreementDispute {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+
+    fn decode_v0(body: &[u8], body_len: usize) -> Option<Result<Self>> {
+        decode_legacy(body, body_len, |legacy: v0::AgreementDispute, dispute: &mut AgreementDispute| {
+            dispute.agreement = legacy.agreement;
+            dispute.initiated_by = legacy.initiated_by;
+            dispute.reason = legacy.reason;
+            dispute.created_at = legacy.created_at;
+            dispute.resolved = legacy.resolved;
+            dispute.resolved_by = legacy.resolved_by;
+            dispute.resolution_notes = legacy.resolution_notes;
+            dispute.resolved_at = legacy.resolved_at;
+        })
+    }
+}
+
+impl VersionedAccount for ShipmentRecord {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.set_purchase_order(None);
+        self.original_eta = 0;
+        self.is_master = false;
+        self.stock_received = false;
+        self.reserved.fill(0);
+    }
+
+    fn decode_v0(body: &[u8], body_len: usize) -> Option<Result<Self>> {
+        decode_legacy(body, body_len, |legacy: v0::ShipmentRecord, shipment: &mut ShipmentRecord| {
+            shipment.tracking_id = legacy.tracking_id;
+            shipment.supplier = legacy.supplier;
+            shipment.destination = legacy.destination;
+            shipment.origin_location = legacy.origin_location;
+            shipment.destination_location = legacy.destination_location;
+            shipment.created_at = legacy.created_at;
+            shipment.estimated_arrival = legacy.estimated_arrival;
+            shipment.status = legacy.status;
+            shipment.products = legacy.products;
+            shipment.verified_by = legacy.verified_by;
+        })
+    }
+}
+
+impl VersionedAccount for TrackingIdRecord {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+}
+
+impl VersionedAccount for ShipmentException {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+}
+
+impl VersionedAccount for EtaRevision {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+}
+
+impl VersionedAccount for ShipmentLocationPage {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+}
+
+impl VersionedAccount for ShipmentCarrier {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+}
+
+impl VersionedAccount for ProofOfDelivery {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.stock_received = false;
+        self.reserved.fill(0);
+    }
+}
+
+impl VersionedAccount for Package {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+}
+
+impl VersionedAccount for SupplyChainEvent {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+
+    fn decode_v0(body: &[u8], body_len: usize) -> Option<Result<Self>> {
+        decode_legacy(body, body_len, |legacy: v0::SupplyChainEvent, event: &mut SupplyChainEvent| {
+            event.event_type = legacy.event_type;
+            event.recorder = legacy.recorder;
+            event.related_entity = legacy.related_entity;
+            event.location = legacy.location;
+            event.timestamp = legacy.timestamp;
+            event.metadata = legacy.metadata;
+            event.created_at = legacy.created_at;
+        })
+    }
+}
+
+impl VersionedAccount for IoTDataRecord {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+
+    fn decode_v0(body: &[u8], body_len: usize) -> Option<Result<Self>> {
+        decode_legacy(body, body_len, |legacy: v0::IoTDataRecord, record: &mut IoTDataRecord| {
+            record.shipment = legacy.shipment;
+            record.data_type = legacy.data_type;
+            record.value = legacy.value;
+            record.timestamp = legacy.timestamp;
+            record.recorder = legacy.recorder;
+            record.is_verified = legacy.is_verified;
+        })
+    }
+}
+
+impl VersionedAccount for ProgramConfig {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+}
+
+impl VersionedAccount for Treasury {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+}
+
+impl VersionedAccount for StoreMember {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+}
+
+impl VersionedAccount for SupplierMember {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+}
+
+impl VersionedAccount for PendingApproval {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+}
+
+impl VersionedAccount for SupplierReview {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+}
+
+impl VersionedAccount for Certification {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+}
+
+impl VersionedAccount for SupplierReverification {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+}
+
+impl VersionedAccount for PriceSchedule {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+}
+
+impl VersionedAccount for StorePriceOverride {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+}
+
+impl VersionedAccount for PurchaseOrder {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+}
+
+impl VersionedAccount for ProductMapping {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn reset_trailer(&mut self, version: u8) {
+        self.version = version;
+        self.reserved.fill(0);
+    }
+}
+
+// Account context for migration; anyone may pay to upgrade an account since the
+// stored fields are carried over unchanged
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: decoded by discriminator in the handler; must be owned by this program
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Events
+#[event]
+pub struct AccountMigratedEvent {
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub migrated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Account layouts the program was first deployed with, before accounts carried a
+/// version. Each layout is recognised by the size baseline accounts were allocated
+/// with. Baseline `ShipmentLocation` accounts were replaced by location pages and are
+/// not migrated.
+mod v0 {
+    use super::*;
+
+    pub trait Layout: AnchorSerialize + AnchorDeserialize {
+        // Allocated account size, including the discriminator
+        const SPACE: usize;
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct Store {
+        pub owner: Pubkey,
+        pub name: String,
+        pub location: String,
+        pub total_products: u64,
+        pub is_active: bool,
+    }
+
+    impl Layout for Store {
+        const SPACE: usize = 8 + 32 + 32 + 32 + 8 + 1;
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct Product {
+        pub store: Pubkey,
+        pub name: String,
+        pub description: String,
+        pub price: u64,
+        pub quantity: u64,
+        pub created_at: i64,
+    }
+
+    impl Layout for Product {
+        const SPACE: usize = 8 + 32 + 32 + 128 + 8 + 8 + 8;
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct Supplier {
+        pub key: Pubkey,
+        pub name: String,
+        pub certification: String,
+        pub description: String,
+        pub products_supplied: u64,
+        pub is_verified: bool,
+        pub rating: u8,
+        pub created_at: i64,
+    }
+
+    impl Layout for Supplier {
+        const SPACE: usize = 8 + 32 + 64 + 128 + 64 + 8 + 1 + 1 + 8;
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct SupplierProduct {
+        pub supplier: Pubkey,
+        pub name: String,
+        pub description: String,
+        pub price: u64,
+        pub available_quantity: u64,
+        pub created_at: i64,
+    }
+
+    impl Layout for SupplierProduct {
+        const SPACE: usize = 8 + 32 + 32 + 128 + 8 + 8 + 8;
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct VerifierCredential {
+        pub authority: Pubkey,
+        pub is_verifier: bool,
+        pub verification_level: u8,
+        pub organization: String,
+    }
+
+    impl Layout for VerifierCredential {
+        const SPACE: usize = 8 + 32 + 1 + 1 + 64;
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct SupplyAgreement {
+        pub supplier: Pubkey,
+        pub store: Pubkey,
+        pub terms: String,
+        pub deadline: i64,
+        pub payment_amount: u64,
+        pub status: u8,
+        pub created_at: i64,
+        pub products: Vec<Pubkey>,
+    }
+
+    impl Layout for SupplyAgreement {
+        const SPACE: usize = 8 + 32 + 32 + 256 + 8 + 8 + 1 + 8 + 64;
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct AgreementDispute {
+        pub agreement: Pubkey,
+        pub initiated_by: Pubkey,
+        pub reason: String,
+        pub created_at: i64,
+        pub resolved: bool,
+        pub resolved_by: Option<Pubkey>,
+        pub resolution_notes: Option<String>,
+        pub resolved_at: Option<i64>,
+    }
+
+    impl Layout for AgreementDispute {
+        const SPACE: usize = 8 + 32 + 32 + 200 + 8 + 1 + (1 + 32) + (1 + 200) + (1 + 8);
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct ShipmentRecord {
+        pub tracking_id: String,
+        pub supplier: Pubkey,
+        pub destination: Pubkey,
+        pub origin_location: String,
+        pub destination_location: String,
+        pub created_at: i64,
+        pub estimated_arrival: i64,
+        pub status: u8,
+        pub products: Vec<Pubkey>,
+        pub verified_by: Vec<Pubkey>,
+    }
+
+    impl Layout for ShipmentRecord {
+        const SPACE: usize = 8 + 64 + 32 + 32 + 64 + 64 + 8 + 8 + 1 + 256 + 256;
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct SupplyChainEvent {
+        pub event_type: u8,
+        pub recorder: Pubkey,
+        pub related_entity: Pubkey,
+        pub location: String,
+        pub timestamp: i64,
+        pub metadata: String,
+        pub created_at: i64,
+    }
+
+    impl Layout for SupplyChainEvent {
+        const SPACE: usize = 8 + 1 + 32 + 32 + 64 + 8 + 256 + 8;
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct IoTDataRecord {
+        pub shipment: Pubkey,
+        pub data_type: u8,
+        pub value: String,
+        pub timestamp: i64,
+        pub recorder: Pubkey,
+        pub is_verified: bool,
+    }
+
+    impl Layout for IoTDataRecord {
+        const SPACE: usize = 8 + 32 + 1 + 128 + 8 + 32 + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Lays out a v0 account body the way the baseline program left it: the fields,
+    // then whatever bytes the allocation held past them
+    fn v0_body<L: v0::Layout>(legacy: &L, slack: u8) -> Vec<u8> {
+        let mut body = legacy.try_to_vec().unwrap();
+        assert!(body.len() <= L::SPACE - 8);
+        body.resize(L::SPACE - 8, slack);
+        body
+    }
+
+    fn zeroed<T: VersionedAccount>(space: usize) -> T {
+        T::deserialize(&mut vec![0u8; space - 8].as_slice()).unwrap()
+    }
+
+    // Upgrades a v0 body with stale slack and checks it lands at the current version
+    // within the current allocation
+    fn upgrade_v0<L: v0::Layout, T: VersionedAccount>(legacy: &L, space: usize) -> T {
+        let (from_version, upgraded) = upgrade_account_data::<T>(&v0_body(legacy, 0xAB), space - 8).unwrap();
+
+        assert_eq!(from_version, 0);
+        assert_eq!(upgraded.version(), CURRENT_ACCOUNT_VERSION);
+        assert!(upgraded.try_to_vec().unwrap().len() <= space - 8);
+        upgraded
+    }
+
+    fn v0_supplier() -> v0::Supplier {
+        v0::Supplier {
+            key: Pubkey::new_unique(),
+            name: "Acme Produce".to_string(),
+            certification: "ISO 22000".to_string(),
+            description: "Fresh fruit and vegetables".to_string(),
+            products_supplied: 12,
+            is_verified: true,
+            rating: 4,
+            created_at: 1_700_000_000,
+        }
+    }
+
+    fn current_supplier() -> Supplier {
+        let mut supplier: Supplier = zeroed(Supplier::space());
+        supplier.key = Pubkey::new_unique();
+        supplier.name = "Acme Produce".to_string();
+        supplier.verification_status = VerificationStatus::Verified as u8;
+        supplier.bump = 254;
+        supplier
+    }
+
+    #[test]
+    fn upgrades_supplier_from_v0() {
+        let legacy = v0_supplier();
+
+        let supplier: Supplier = upgrade_v0(&legacy, Supplier::space());
+
+        assert_eq!(supplier.key, legacy.key);
+        assert_eq!(supplier.name, legacy.name);
+        assert_eq!(supplier.certification, legacy.certification);
+        assert_eq!(supplier.description, legacy.description);
+        assert_eq!(supplier.products_supplied, 12);
+        assert!(supplier.is_verified());
+        assert_eq!(supplier.rating, 4);
+        assert_eq!(supplier.created_at, 1_700_000_000);
+        assert_eq!(supplier.bump, 0);
+        assert_eq!(supplier.registered_key, Pubkey::default());
+        assert_eq!(supplier.shipments_delivered, 0);
+        assert!(supplier.reserved.iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn upgrades_shipment_from_v0() {
+        let legacy = v0::ShipmentRecord {
+            tracking_id: "TRK-0001".to_string(),
+            supplier: Pubkey::new_unique(),
+            destination: Pubkey::new_unique(),
+            origin_location: "Rotterdam".to_string(),
+            destination_location: "Berlin".to_string(),
+            created_at: 1_700_000_000,
+            estimated_arrival: 1_700_100_000,
+            status: ShipmentStatus::Delivered as u8,
+            products: vec![Pubkey::new_unique(); 8],
+            verified_by: vec![Pubkey::new_unique(); 2],
+        };
+
+        let shipment: ShipmentRecord = upgrade_v0(&legacy, ShipmentRecord::space());
+
+        assert_eq!(shipment.tracking_id, legacy.tracking_id);
+        assert_eq!(shipment.supplier, legacy.supplier);
+        assert_eq!(shipment.destination, legacy.destination);
+        assert_eq!(shipment.origin_location, legacy.origin_location);
+        assert_eq!(shipment.destination_location, legacy.destination_location);
+        assert_eq!(shipment.estimated_arrival, 1_700_100_000);
+        assert_eq!(shipment.committed_eta(), 1_700_100_000);
+        assert_eq!(shipment.status, ShipmentStatus::Delivered as u8);
+        assert_eq!(shipment.products, legacy.products);
+        assert_eq!(shipment.verified_by, legacy.verified_by);
+        assert!(shipment.route_plan.is_none());
+        assert!(shipment.latest_location.is_none());
+        assert!(shipment.agreement.is_none());
+        assert!(shipment.child_shipments.is_empty());
+        assert!(shipment.purchase_order().is_none());
+        assert!(!shipment.is_master);
+    }
+
+    #[test]
+    fn upgrades_agreement_from_v0() {
+        let legacy = v0::SupplyAgreement {
+            supplier: Pubkey::new_unique(),
+            store: Pubkey::new_unique(),
+            terms: "Net 30, FOB destination".to_string(),
+            deadline: 1_700_500_000,
+            payment_amount: 5_000_000,
+            status: AgreementStatus::Active as u8,
+            created_at: 1_700_000_000,
+            products: vec![Pubkey::new_unique(); 2],
+        };
+
+        let agreement: SupplyAgreement = upgrade_v0(&legacy, SupplyAgreement::space());
+
+        assert_eq!(agreement.supplier, legacy.supplier);
+        assert_eq!(agreement.store, legacy.store);
+        assert_eq!(agreement.terms, legacy.terms);
+        assert_eq!(agreement.deadline, 1_700_500_000);
+        assert_eq!(agreement.payment_amount, 5_000_000);
+        assert_eq!(agreement.status, AgreementStatus::Active as u8);
+        assert_eq!(agreement.products, legacy.products);
+        assert_eq!(agreement.nonce, 0);
+        assert_eq!(agreement.escrow_balance, 0);
+        assert!(agreement.required_certification().is_none());
+    }
+
+    #[test]
+    fn upgrades_dispute_from_v0() {
+        let legacy = v0::AgreementDispute {
+            agreement: Pubkey::new_unique(),
+            initiated_by: Pubkey::new_unique(),
+            reason: "Short delivery".to_string(),
+            created_at: 1_700_000_000,
+            resolved: true,
+            resolved_by: Some(Pubkey::new_unique()),
+            resolution_notes: Some("Credit issued".to_string()),
+            resolved_at: Some(1_700_200_000),
+        };
+
+        let dispute: AgreementDispute = upgrade_v0(&legacy, AgreementDispute::space());
+
+        assert_eq!(dispute.agreement, legacy.agreement);
+        assert_eq!(dispute.reason, legacy.reason);
+        assert_eq!(dispute.resolved_by, legacy.resolved_by);
+        assert_eq!(dispute.resolution_notes, legacy.resolution_notes);
+        assert_eq!(dispute.resolved_at, Some(1_700_200_000));
+    }
+
+    #[test]
+    fn upgrades_every_baseline_account_type_from_v0() {
+        let store: Store = upgrade_v0(&v0::Store {
+            owner: Pubkey::new_unique(),
+            name: "Main Street".to_string(),
+            location: "Utrecht".to_string(),
+            total_products: 40,
+            is_active: true,
+        }, Store::space());
+        assert_eq!(store.name, "Main Street");
+        assert!(store.is_active);
+        assert_eq!(store.approvals_required, 0);
+
+        let product: Product = upgrade_v0(&v0::Product {
+            store: store.owner,
+            name: "Apples".to_string(),
+            description: "Per kilo".to_string(),
+            price: 250,
+            quantity: 100,
+            created_at: 1_700_000_000,
+        }, Product::space());
+        assert_eq!(product.price, 250);
+        assert_eq!(product.quantity, 100);
+
+        let catalog_entry: SupplierProduct = upgrade_v0(&v0::SupplierProduct {
+            supplier: Pubkey::new_unique(),
+            name: "Pears".to_string(),
+            description: "Crate of 12".to_string(),
+            price: 900,
+            available_quantity: 30,
+            created_at: 1_700_000_000,
+        }, SupplierProduct::space());
+        assert_eq!(catalog_entry.available_quantity, 30);
+        assert!(!catalog_entry.is_discontinued);
+
+        let credential: VerifierCredential = upgrade_v0(&v0::VerifierCredential {
+            authority: Pubkey::new_unique(),
+            is_verifier: true,
+            verification_level: 2,
+            organization: "Port Authority".to_string(),
+        }, VerifierCredential::space());
+        assert!(credential.is_verifier);
+        assert_eq!(credential.organization, "Port Authority");
+
+        let event: SupplyChainEvent = upgrade_v0(&v0::SupplyChainEvent {
+            event_type: EventType::QualityCheck as u8,
+            recorder: Pubkey::new_unique(),
+            related_entity: Pubkey::new_unique(),
+            location: "Dock 4".to_string(),
+            timestamp: 1_700_000_000,
+            metadata: "{\"passed\":true}".to_string(),
+            created_at: 1_700_000_100,
+        }, SupplyChainEvent::space());
+        assert_eq!(event.metadata, "{\"passed\":true}");
+        assert_eq!(event.created_at, 1_700_000_100);
+
+        let reading: IoTDataRecord = upgrade_v0(&v0::IoTDataRecord {
+            shipment: Pubkey::new_unique(),
+            data_type: IoTDataType::Temperature as u8,
+            value: "4.5C".to_string(),
+            timestamp: 1_700_000_000,
+            recorder: Pubkey::new_unique(),
+            is_verified: true,
+        }, IoTDataRecord::space());
+        assert_eq!(reading.value, "4.5C");
+        assert!(reading.is_verified);
+    }
+
+    #[test]
+    fn v0_layouts_are_smaller_than_current_layouts() {
+        assert!(<v0::Store as v0::Layout>::SPACE < Store::space());
+        assert!(<v0::Product as v0::Layout>::SPACE < Product::space());
+        assert!(<v0::Supplier as v0::Layout>::SPACE < Supplier::space());
+        assert!(<v0::SupplierProduct as v0::Layout>::SPACE < SupplierProduct::space());
+        assert!(<v0::VerifierCredential as v0::Layout>::SPACE < VerifierCredential::space());
+        assert!(<v0::SupplyAgreement as v0::Layout>::SPACE < SupplyAgreement::space());
+        assert!(<v0::AgreementDispute as v0::Layout>::SPACE < AgreementDispute::space());
+        assert!(<v0::ShipmentRecord as v0::Layout>::SPACE < ShipmentRecord::space());
+        assert!(<v0::SupplyChainEvent as v0::Layout>::SPACE < SupplyChainEvent::space());
+        assert!(<v0::IoTDataRecord as v0::Layout>::SPACE < IoTDataRecord::space());
+    }
+
+    #[test]
+    fn upgraded_account_is_not_migrated_twice() {
+        let supplier: Supplier = upgrade_v0(&v0_supplier(), Supplier::space());
+        let body = supplier.try_to_vec().unwrap();
+
+        let result = upgrade_account_data::<Supplier>(&body, Supplier::space() - 8);
+
+        assert_eq!(result.err(), Some(SupplyChainError::AccountAlreadyMigrated.into()));
+    }
+
+    #[test]
+    fn rejects_version_0_outside_v0_layouts() {
+        let mut supplier = current_supplier();
+        supplier.version = 0;
+        let body = supplier.try_to_vec().unwrap();
+
+        let result = upgrade_account_data::<Supplier>(&body, Supplier::space() - 8);
+
+        assert_eq!(result.err(), Some(SupplyChainError::UnsupportedAccountVersion.into()));
+    }
+
+    #[test]
+    fn rejects_current_version() {
+        let mut supplier = current_supplier();
+        supplier.version = CURRENT_ACCOUNT_VERSION;
+        let body = supplier.try_to_vec().unwrap();
+
+        let result = upgrade_account_data::<Supplier>(&body, Supplier::space() - 8);
+
+        assert_eq!(result.err(), Some(SupplyChainError::AccountAlreadyMigrated.into()));
+    }
+
+    #[test]
+    fn rejects_unknown_future_version() {
+        let mut supplier = current_supplier();
+        supplier.version = CURRENT_ACCOUNT_VERSION + 1;
+        let body = supplier.try_to_vec().unwrap();
+
+        let result = upgrade_account_data::<Supplier>(&body, Supplier::space() - 8);
+
+        assert_eq!(result.err(), Some(SupplyChainError::UnsupportedAccountVersion.into()));
+    }
+}
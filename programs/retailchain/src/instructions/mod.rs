@@ -1,19 +1,45 @@
 pub mod supplier;
 pub mod shipment;
+pub mod consolidation;
+pub mod delivery;
 pub mod agreement;
+pub mod package;
+pub mod migration;
+pub mod config;
+pub mod store_member;
+pub mod supplier_member;
+pub mod approval;
+pub mod certification;
+pub mod pricing;
+pub mod purchase_order;
+pub mod product_mapping;
 pub mod store;
 pub mod product;
 pub mod iot;
 pub mod events;
+pub mod verifier;
 
 // Re-export instruction handlers for cleaner imports in lib.rs
 pub use supplier::*;
 pub use shipment::*;
+pub use consolidation::*;
+pub use delivery::*;
 pub use agreement::*;
+pub use package::*;
+pub use migration::*;
+pub use config::*;
+pub use store_member::*;
+pub use supplier_member::*;
+pub use approval::*;
+pub use certification::*;
+pub use pricing::*;
+pub use purchase_order::*;
+pub use product_mapping::*;
 pub use store::*;
 pub use product::*;
 pub use iot::*;
 pub use events::*;
+pub use verifier::*;
 
 // Common instruction context utilities
 use anchor_lang::prelude::*;
@@ -22,18 +48,69 @@ use crate::errors::SupplyChainError;
 
 // Shared validation functions that may be used across different instruction modules
 
-/// Validates that a user has permission to modify a store
+/// Validates that a user has permission to act for a store: the owner, or a
+/// staff member holding any of the given roles
 pub fn validate_store_authority(
     authority: &Signer,
     store: &Account<Store>,
+    store_member: &Option<Account<StoreMember>>,
+    roles: u8,
 ) -> Result<()> {
     require!(
-        store.owner == authority.key(),
+        has_store_role(store, store_member, authority.key(), roles),
         SupplyChainError::Unauthorized
     );
     Ok(())
 }
 
+/// Returns true if `authority` is the store owner or a member of the store holding
+/// any of the given roles; usable directly in account constraints
+pub fn has_store_role(
+    store: &Account<Store>,
+    store_member: &Option<Account<StoreMember>>,
+    authority: Pubkey,
+    roles: u8,
+) -> bool {
+    store.owner == authority ||
+        store_member.as_ref().is_some_and(|member| member.grants(store.key(), authority, roles))
+}
+
+/// Validates that a user has permission to act for a supplier: the supplier's key,
+/// or an operator holding any of the given roles
+pub fn validate_supplier_authority(
+    authority: &Signer,
+    supplier: &Account<Supplier>,
+    supplier_member: &Option<Account<SupplierMember>>,
+    roles: u8,
+) -> Result<()> {
+    require!(
+        has_supplier_role(supplier, supplier_member, authority.key(), roles),
+        SupplyChainError::Unauthorized
+    );
+    Ok(())
+}
+
+/// Returns true if `authority` is the supplier's key or an operator of the supplier
+/// holding any of the given roles; usable directly in account constraints
+pub fn has_supplier_role(
+    supplier: &Account<Supplier>,
+    supplier_member: &Option<Account<SupplierMember>>,
+    authority: Pubkey,
+    roles: u8,
+) -> bool {
+    supplier.key == authority ||
+        supplier_member.as_ref().is_some_and(|member| member.grants(supplier.key(), authority, roles))
+}
+
+/// Validates that a string fits within its maximum stored length
+pub fn validate_string_length(value: &str, max_len: usize) -> Result<()> {
+    require!(
+        value.len() <= max_len,
+        SupplyChainError::StringTooLong
+    );
+    Ok(())
+}
+
 /// Validates that a store is active
 pub fn validate_store_active(store: &Account<Store>) -> Result<()> {
     require!(store.is_active, SupplyChainError::StoreInactive);
@@ -43,7 +120,25 @@ pub fn validate_store_active(store: &Account<Store>) -> Result<()> {
 /// Validates that a supplier is verified
 pub fn validate_supplier_verified(supplier: &Account<Supplier>) -> Result<()> {
     require!(
-        supplier.is_verified,
+        supplier.is_verified(),
+        SupplyChainError::VerificationRequired
+    );
+    Ok(())
+}
+
+/// Validates that a supplier can keep trading: verified, or awaiting re-verification
+/// with its grace period still running
+pub fn validate_supplier_in_good_standing(
+    supplier: &Account<Supplier>,
+    reverification: &Option<Account<SupplierReverification>>,
+    current_time: i64,
+) -> Result<()> {
+    let within_grace = supplier.verification_status == VerificationStatus::PendingReverification as u8 &&
+        reverification.as_ref().is_some_and(|reverification| {
+            reverification.supplier == supplier.key() && current_time < reverification.grace_ends_at
+        });
+    require!(
+        supplier.is_verified() || within_grace,
         SupplyChainError::VerificationRequired
     );
     Ok(())
@@ -0,0 +1,387 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::SupplyChainError;
+use super::{validate_string_length, has_store_role, has_supplier_role};
+use super::store_member::{StoreMember, STORE_ROLE_RECEIVING};
+use super::config::{ProgramConfig, CONFIG_SEED};
+use super::supplier_member::{SupplierMember, SUPPLIER_ROLE_SHIPPING};
+
+// Package creation and nesting.
+// While a package is packed inside another, its custody, status, location and
+// shipment are those of its outermost container: scans only touch the root
+// package, and the values are copied back onto a package when it is unpacked.
+pub fn create_package(
+    ctx: Context<CreatePackage>,
+    kind: u8,
+    label: String
+) -> Result<()> {
+    let package = &mut ctx.accounts.package;
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    require!(
+        kind <= PackageKind::Container as u8,
+        SupplyChainError::InvalidData
+    );
+    
+    require!(
+        !label.is_empty(),
+        SupplyChainError::InvalidData
+    );
+    validate_string_length(&label, MAX_LABEL_LEN)?;
+    
+    package.owner = ctx.accounts.supplier.key();
+    package.label = label;
+    package.kind = kind;
+    package.parent = None;
+    package.contents = Vec::new();
+    package.shipment = None;
+    package.custodian = ctx.accounts.authority.key();
+    package.status = PackageStatus::Staged as u8;
+    package.last_location = String::new();
+    package.last_scanned_at = current_time;
+    package.created_at = current_time;
+    package.bump = ctx.bumps.package;
+    package.version = CURRENT_ACCOUNT_VERSION;
+    
+    emit!(PackageCreatedEvent {
+        package: package.key(),
+        owner: package.owner,
+        kind,
+        label: package.label.clone(),
+        timestamp: current_time,
+    });
+    
+    Ok(())
+}
+
+pub fn pack(
+    ctx: Context<PackPackage>
+) -> Result<()> {
+    let parent = &mut ctx.accounts.parent;
+    let child = &mut ctx.accounts.child;
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    require!(
+        parent.key() != child.key() && child.parent.is_none(),
+        SupplyChainError::InvalidRelationship
+    );
+    
+    // Smaller units go inside larger ones: cases on pallets, pallets in containers
+    require!(
+        child.kind < parent.kind,
+        SupplyChainError::InvalidData
+    );
+    
+    // A packed package travels with its container's shipment
+    require!(
+        child.shipment.is_none() || child.shipment == parent.shipment,
+        SupplyChainError::ShipmentNotRelated
+    );
+    
+    require!(
+        parent.contents.len() < MAX_PACKAGE_CONTENTS,
+        SupplyChainError::ResourceLimitExceeded
+    );
+    
+    parent.contents.push(child.key());
+    child.parent = Some(parent.key());
+    child.shipment = None;
+    
+    emit!(PackagePackedEvent {
+        parent: parent.key(),
+        child: child.key(),
+        packed_by: ctx.accounts.authority.key(),
+        timestamp: current_time,
+    });
+    
+    Ok(())
+}
+
+pub fn unpack(
+    ctx: Context<UnpackPackage>
+) -> Result<()> {
+    let parent = &mut ctx.accounts.parent;
+    let child = &mut ctx.accounts.child;
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    // Remove the child from its container
+    let position = parent.contents.iter()
+        .position(|content| *content == child.key())
+        .ok_or(SupplyChainError::EntityNotFound)?;
+    parent.contents.remove(position);
+    
+    // The child takes over the state it implicitly shared with the container
+    child.parent = None;
+    child.shipment = parent.shipment;
+    child.custodian = parent.custodian;
+    child.status = parent.status;
+    child.last_location = parent.last_location.clone();
+    child.last_scanned_at = parent.last_scanned_at;
+    
+    emit!(PackageUnpackedEvent {
+        parent: parent.key(),
+        child: child.key(),
+        unpacked_by: ctx.accounts.authority.key(),
+        timestamp: current_time,
+    });
+    
+    Ok(())
+}
+
+pub fn attach_to_shipment(
+    ctx: Context<AttachToShipment>
+) -> Result<()> {
+    let package = &mut ctx.accounts.package;
+    let shipment = &ctx.accounts.shipment;
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    // Packages can only be loaded before the shipment is delivered
+    require!(
+        shipment.status == ShipmentStatus::Created as u8 ||
+        shipment.status == ShipmentStatus::InTransit as u8,
+        SupplyChainError::InvalidStatusTransition
+    );
+    
+    require!(
+        package.shipment.is_none(),
+        SupplyChainError::DuplicateEntry
+    );
+    
+    package.shipment = Some(shipment.key());
+    
+    emit!(PackageAttachedEvent {
+        package: package.key(),
+        shipment: shipment.key(),
+        attached_by: ctx.accounts.authority.key(),
+        timestamp: current_time,
+    });
+    
+    Ok(())
+}
+
+pub fn scan_package(
+    ctx: Context<ScanPackage>,
+    status: u8,
+    location: String
+) -> Result<()> {
+    let package = &mut ctx.accounts.package;
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    require!(
+        status <= PackageStatus::Delivered as u8,
+        SupplyChainError::InvalidData
+    );
+    
+    validate_string_length(&location, MAX_LOCATION_LEN)?;
+    
+    // Scanning hands custody to the scanner; nested packages follow implicitly
+    package.custodian = ctx.accounts.authority.key();
+    package.status = status;
+    package.last_location = location.clone();
+    package.last_scanned_at = current_time;
+    
+    emit!(PackageScannedEvent {
+        package: package.key(),
+        custodian: package.custodian,
+        status,
+        location,
+        nested_packages: package.contents.len() as u8,
+        timestamp: current_time,
+    });
+    
+    Ok(())
+}
+
+// Account contexts for package operations
+#[derive(Accounts)]
+#[instruction(kind: u8, label: String)]
+pub struct CreatePackage<'info> {
+    // Longer values cannot be used as PDA seeds
+    #[account(
+        mut,
+        constraint = label.len() <= MAX_LABEL_LEN @ SupplyChainError::StringTooLong
+    )]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_SHIPPING)
+    )]
+    pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    #[account(
+        init,
+        payer = authority,
+        space = Package::space(),
+        seeds = [PACKAGE_SEED, supplier.key().as_ref(), label.as_bytes()],
+        bump
+    )]
+    pub package: Account<'info, Package>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+// Packages are packed bottom-up: the parent must not itself be packed yet
+#[derive(Accounts)]
+pub struct PackPackage<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = parent.parent.is_none(),
+        constraint = parent.custodian == authority.key()
+    )]
+    pub parent: Account<'info, Package>,
+    #[account(
+        mut,
+        constraint = child.custodian == authority.key()
+    )]
+    pub child: Account<'info, Package>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UnpackPackage<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = parent.parent.is_none(),
+        constraint = parent.custodian == authority.key()
+    )]
+    pub parent: Account<'info, Package>,
+    #[account(
+        mut,
+        constraint = child.parent == Some(parent.key())
+    )]
+    pub child: Account<'info, Package>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AttachToShipment<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_SHIPPING)
+    )]
+    pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    #[account(
+        constraint = shipment.supplier == supplier.key()
+    )]
+    pub shipment: Account<'info, ShipmentRecord>,
+    #[account(
+        mut,
+        constraint = package.parent.is_none(),
+        constraint = package.custodian == authority.key()
+    )]
+    pub package: Account<'info, Package>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// Only outermost packages are scanned. The scanner must be the current custodian,
+// a credentialed verifier (dock operator/carrier) or the receiving store's owner or
+// receiving staff.
+#[derive(Accounts)]
+pub struct ScanPackage<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = package.parent.is_none(),
+        constraint = package.custodian == authority.key() ||
+                    (authority_credentials.is_some() &&
+                     authority_credentials.as_ref().unwrap().authority == authority.key() &&
+                     authority_credentials.as_ref().unwrap().is_verifier) ||
+                    (shipment.is_some() && store.is_some() &&
+                     package.shipment == Some(shipment.as_ref().unwrap().key()) &&
+                     shipment.as_ref().unwrap().destination == store.as_ref().unwrap().key() &&
+                     has_store_role(store.as_ref().unwrap(), &store_member, authority.key(), STORE_ROLE_RECEIVING))
+    )]
+    pub package: Account<'info, Package>,
+    pub authority_credentials: Option<Account<'info, VerifierCredential>>,
+    pub shipment: Option<Account<'info, ShipmentRecord>>,
+    pub store: Option<Account<'info, Store>>,
+    pub store_member: Option<Account<'info, StoreMember>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// Event definitions
+#[event]
+pub struct PackageCreatedEvent {
+    pub package: Pubkey,
+    pub owner: Pubkey,
+    pub kind: u8,
+    pub label: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PackagePackedEvent {
+    pub parent: Pubkey,
+    pub child: Pubkey,
+    pub packed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PackageUnpackedEvent {
+    pub parent: Pubkey,
+    pub child: Pubkey,
+    pub unpacked_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PackageAttachedEvent {
+    pub package: Pubkey,
+    pub shipment: Pubkey,
+    pub attached_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PackageScannedEvent {
+    pub package: Pubkey,
+    pub custodian: Pubkey,
+    pub status: u8,
+    pub location: String,
+    pub nested_packages: u8,
+    pub timestamp: i64,
+}
+
+// Package account structure, addressed by ["package", supplier, label]
+pub const PACKAGE_SEED: &[u8] = b"package";
+pub const MAX_PACKAGE_CONTENTS: usize = 16;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Package {
+    pub owner: Pubkey,
+    #[max_len(MAX_LABEL_LEN)]
+    pub label: String,
+    pub kind: u8,
+    pub parent: Option<Pubkey>,
+    #[max_len(MAX_PACKAGE_CONTENTS)]
+    pub contents: Vec<Pubkey>,
+    pub shipment: Option<Pubkey>,
+    pub custodian: Pubkey,
+    pub status: u8,
+    #[max_len(MAX_LOCATION_LEN)]
+    pub last_location: String,
+    pub last_scanned_at: i64,
+    pub created_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl Package {
+    pub fn space() -> usize {
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+}
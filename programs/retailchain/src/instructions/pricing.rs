@@ -0,0 +1,418 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::SupplyChainError;
+use super::has_supplier_role;
+use super::supplier::{SupplierProduct, SUPPLIER_PRODUCT_SEED};
+use super::supplier_member::{SupplierMember, SUPPLIER_ROLE_CATALOG, SUPPLIER_ROLE_CONTRACTS};
+use super::config::{ProgramConfig, CONFIG_SEED};
+
+// Tiered and negotiated catalog pricing.
+// A product's list price can be lowered by a volume price schedule and by a price
+// negotiated with a single store, each valid within its own time window. `quote`
+// returns the lowest unit price that applies.
+pub fn set_price_schedule(
+    ctx: Context<SetPriceSchedule>,
+    price_breaks: Vec<PriceBreak>,
+    valid_from: i64,
+    valid_until: Option<i64>
+) -> Result<()> {
+    require!(
+        !ctx.accounts.supplier_product.is_discontinued,
+        SupplyChainError::ProductDiscontinued
+    );
+
+    require!(
+        price_breaks.len() <= MAX_PRICE_BREAKS,
+        SupplyChainError::ResourceLimitExceeded
+    );
+
+    // Breaks are listed by strictly increasing quantity threshold
+    require!(
+        price_breaks.iter().all(|price_break| price_break.min_quantity > 0 && price_break.unit_price > 0) &&
+        price_breaks.windows(2).all(|pair| pair[0].min_quantity < pair[1].min_quantity),
+        SupplyChainError::InvalidData
+    );
+
+    validate_window(valid_from, valid_until)?;
+
+    ctx.accounts.supplier_product.has_price_schedule = true;
+
+    let schedule = &mut ctx.accounts.price_schedule;
+    schedule.supplier_product = ctx.accounts.supplier_product.key();
+    schedule.price_breaks = price_breaks;
+    schedule.valid_from = valid_from;
+    schedule.valid_until = valid_until;
+    schedule.updated_at = Clock::get()?.unix_timestamp;
+    schedule.bump = ctx.bumps.price_schedule;
+    schedule.version = CURRENT_ACCOUNT_VERSION;
+
+    emit!(PriceScheduleUpdatedEvent {
+        supplier_product: schedule.supplier_product,
+        breaks: schedule.price_breaks.len() as u8,
+        valid_from,
+        valid_until,
+        updated_by: ctx.accounts.authority.key(),
+        timestamp: schedule.updated_at,
+    });
+
+    Ok(())
+}
+
+pub fn set_store_price_override(
+    ctx: Context<SetStorePriceOverride>,
+    unit_price: u64,
+    valid_from: i64,
+    valid_until: Option<i64>
+) -> Result<()> {
+    require!(
+        !ctx.accounts.supplier_product.is_discontinued,
+        SupplyChainError::ProductDiscontinued
+    );
+
+    require!(
+        unit_price > 0,
+        SupplyChainError::InvalidPrice
+    );
+
+    validate_window(valid_from, valid_until)?;
+
+    let price_override = &mut ctx.accounts.price_override;
+    price_override.supplier_product = ctx.accounts.supplier_product.key();
+    price_override.store = ctx.accounts.store.key();
+    price_override.unit_price = unit_price;
+    price_override.valid_from = valid_from;
+    price_override.valid_until = valid_until;
+    price_override.set_by = ctx.accounts.authority.key();
+    price_override.bump = ctx.bumps.price_override;
+    price_override.version = CURRENT_ACCOUNT_VERSION;
+
+    emit!(StorePriceOverrideUpdatedEvent {
+        supplier_product: price_override.supplier_product,
+        store: price_override.store,
+        unit_price: Some(unit_price),
+        valid_from,
+        valid_until,
+        updated_by: price_override.set_by,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn remove_store_price_override(
+    ctx: Context<RemoveStorePriceOverride>
+) -> Result<()> {
+    let price_override = &ctx.accounts.price_override;
+
+    emit!(StorePriceOverrideUpdatedEvent {
+        supplier_product: price_override.supplier_product,
+        store: price_override.store,
+        unit_price: None,
+        valid_from: price_override.valid_from,
+        valid_until: price_override.valid_until,
+        updated_by: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// View instruction: the effective unit price is returned as return data
+pub fn quote(
+    ctx: Context<Quote>,
+    quantity: u64
+) -> Result<u64> {
+    let supplier_product = &ctx.accounts.supplier_product;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    require!(
+        !supplier_product.is_discontinued,
+        SupplyChainError::ProductDiscontinued
+    );
+
+    require!(
+        quantity > 0,
+        SupplyChainError::InvalidQuantity
+    );
+
+    Ok(effective_unit_price(
+        supplier_product,
+        ctx.accounts.price_schedule.as_deref(),
+        ctx.accounts.price_override.as_deref(),
+        quantity,
+        current_time
+    ))
+}
+
+// Lowest of the list price, the schedule's break for `quantity` and the store's
+// negotiated price, counting only the ones valid at `timestamp`
+pub fn effective_unit_price(
+    supplier_product: &SupplierProduct,
+    price_schedule: Option<&PriceSchedule>,
+    price_override: Option<&StorePriceOverride>,
+    quantity: u64,
+    timestamp: i64
+) -> u64 {
+    let mut unit_price = supplier_product.price;
+
+    if let Some(schedule) = price_schedule {
+        if schedule.is_valid_at(timestamp) {
+            if let Some(price) = schedule.unit_price_for(quantity) {
+                unit_price = unit_price.min(price);
+            }
+        }
+    }
+
+    if let Some(price_override) = price_override {
+        if price_override.is_valid_at(timestamp) {
+            unit_price = unit_price.min(price_override.unit_price);
+        }
+    }
+
+    unit_price
+}
+
+fn validate_window(valid_from: i64, valid_until: Option<i64>) -> Result<()> {
+    require!(
+        valid_until.map_or(true, |until| until > valid_from),
+        SupplyChainError::InvalidTimestamp
+    );
+    Ok(())
+}
+
+fn is_within_window(timestamp: i64, valid_from: i64, valid_until: Option<i64>) -> bool {
+    timestamp >= valid_from && valid_until.map_or(true, |until| timestamp < until)
+}
+
+// Account contexts for pricing operations
+#[derive(Accounts)]
+pub struct SetPriceSchedule<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_CATALOG)
+    )]
+    pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    #[account(
+        mut,
+        seeds = [SUPPLIER_PRODUCT_SEED, supplier.key().as_ref(), supplier_product.name.as_bytes()],
+        bump = supplier_product.bump
+    )]
+    pub supplier_product: Account<'info, SupplierProduct>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = PriceSchedule::space(),
+        seeds = [PRICE_SCHEDULE_SEED, supplier_product.key().as_ref()],
+        bump
+    )]
+    pub price_schedule: Account<'info, PriceSchedule>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+// Negotiated prices are agreed by the supplier's contracts operators
+#[derive(Accounts)]
+pub struct SetStorePriceOverride<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_CONTRACTS)
+    )]
+    pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    #[account(
+        seeds = [SUPPLIER_PRODUCT_SEED, supplier.key().as_ref(), supplier_product.name.as_bytes()],
+        bump = supplier_product.bump
+    )]
+    pub supplier_product: Account<'info, SupplierProduct>,
+    pub store: Account<'info, Store>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = StorePriceOverride::space(),
+        seeds = [PRICE_OVERRIDE_SEED, supplier_product.key().as_ref(), store.key().as_ref()],
+        bump
+    )]
+    pub price_override: Account<'info, StorePriceOverride>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveStorePriceOverride<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_CONTRACTS)
+    )]
+    pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    #[account(
+        seeds = [SUPPLIER_PRODUCT_SEED, supplier.key().as_ref(), supplier_product.name.as_bytes()],
+        bump = supplier_product.bump
+    )]
+    pub supplier_product: Account<'info, SupplierProduct>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [PRICE_OVERRIDE_SEED, supplier_product.key().as_ref(), price_override.store.as_ref()],
+        bump = price_override.bump
+    )]
+    pub price_override: Account<'info, StorePriceOverride>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct Quote<'info> {
+    pub supplier_product: Account<'info, SupplierProduct>,
+    pub store: Account<'info, Store>,
+    #[account(
+        seeds = [PRICE_SCHEDULE_SEED, supplier_product.key().as_ref()],
+        bump = price_schedule.bump
+    )]
+    pub price_schedule: Option<Account<'info, PriceSchedule>>,
+    #[account(
+        seeds = [PRICE_OVERRIDE_SEED, supplier_product.key().as_ref(), store.key().as_ref()],
+        bump = price_override.bump
+    )]
+    pub price_override: Option<Account<'info, StorePriceOverride>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// Events
+#[event]
+pub struct PriceScheduleUpdatedEvent {
+    pub supplier_product: Pubkey,
+    pub breaks: u8,
+    pub valid_from: i64,
+    pub valid_until: Option<i64>,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+// `unit_price` is None when the override is removed
+#[event]
+pub struct StorePriceOverrideUpdatedEvent {
+    pub supplier_product: Pubkey,
+    pub store: Pubkey,
+    pub unit_price: Option<u64>,
+    pub valid_from: i64,
+    pub valid_until: Option<i64>,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+// Accounts
+// Price schedules are addressed by ["price_schedule", supplier_product] and
+// store overrides by ["price_override", supplier_product, store]
+pub const PRICE_SCHEDULE_SEED: &[u8] = b"price_schedule";
+pub const PRICE_OVERRIDE_SEED: &[u8] = b"price_override";
+
+pub const MAX_PRICE_BREAKS: usize = 8;
+
+// Unit price that applies from `min_quantity` units upwards
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct PriceBreak {
+    pub min_quantity: u64,
+    pub unit_price: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PriceSchedule {
+    pub supplier_product: Pubkey,
+    #[max_len(MAX_PRICE_BREAKS)]
+    pub price_breaks: Vec<PriceBreak>,
+    pub valid_from: i64,
+    pub valid_until: Option<i64>,
+    pub updated_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl PriceSchedule {
+    pub fn space() -> usize {
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+
+    pub fn is_valid_at(&self, timestamp: i64) -> bool {
+        is_within_window(timestamp, self.valid_from, self.valid_until)
+    }
+
+    // Unit price of the highest break the quantity reaches, if any
+    pub fn unit_price_for(&self, quantity: u64) -> Option<u64> {
+        self.price_breaks.iter()
+            .rev()
+            .find(|price_break| quantity >= price_break.min_quantity)
+            .map(|price_break| price_break.unit_price)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct StorePriceOverride {
+    pub supplier_product: Pubkey,
+    pub store: Pubkey,
+    pub unit_price: u64,
+    pub valid_from: i64,
+    pub valid_until: Option<i64>,
+    pub set_by: Pubkey,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl StorePriceOverride {
+    pub fn space() -> usize {
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+
+    pub fn is_valid_at(&self, timestamp: i64) -> bool {
+        is_within_window(timestamp, self.valid_from, self.valid_until)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule_with(breaks: &[(u64, u64)]) -> PriceSchedule {
+        let mut schedule = PriceSchedule::deserialize(
+            &mut vec![0u8; PriceSchedule::space() - 8].as_slice()
+        ).unwrap();
+        schedule.price_breaks = breaks.iter()
+            .map(|&(min_quantity, unit_price)| PriceBreak { min_quantity, unit_price })
+            .collect();
+        schedule
+    }
+
+    #[test]
+    fn quantity_below_first_break_has_no_break_price() {
+        let schedule = schedule_with(&[(10, 90), (50, 80)]);
+
+        assert_eq!(schedule.unit_price_for(9), None);
+    }
+
+    #[test]
+    fn break_applies_from_its_exact_threshold() {
+        let schedule = schedule_with(&[(10, 90), (50, 80)]);
+
+        assert_eq!(schedule.unit_price_for(10), Some(90));
+        assert_eq!(schedule.unit_price_for(49), Some(90));
+        assert_eq!(schedule.unit_price_for(50), Some(80));
+    }
+
+    #[test]
+    fn highest_reached_break_wins() {
+        let schedule = schedule_with(&[(10, 90), (50, 80)]);
+
+        assert_eq!(schedule.unit_price_for(1_000), Some(80));
+    }
+}
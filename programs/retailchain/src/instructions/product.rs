@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::SupplyChainError;
+use super::{has_store_role, validate_store_active, validate_string_length};
+use super::store_member::{StoreMember, STORE_ROLE_INVENTORY};
+use super::config::{ProgramConfig, CONFIG_SEED};
+
+// Store product inventory.
+// Products are managed by the store owner or staff holding the inventory role.
+pub fn add_product(
+    ctx: Context<AddProduct>,
+    name: String,
+    description: String,
+    price: u64,
+    quantity: u64
+) -> Result<()> {
+    validate_store_active(&ctx.accounts.store)?;
+    validate_string_length(&name, MAX_NAME_LEN)?;
+    validate_string_length(&description, MAX_DESCRIPTION_LEN)?;
+
+    require!(
+        price > 0,
+        SupplyChainError::InvalidPrice
+    );
+
+    let current_time = Clock::get()?.unix_timestamp;
+    let product = &mut ctx.accounts.product;
+    product.store = ctx.accounts.store.key();
+    product.name = name;
+    product.description = description;
+    product.price = price;
+    product.quantity = quantity;
+    product.created_at = current_time;
+    product.version = CURRENT_ACCOUNT_VERSION;
+
+    let store = &mut ctx.accounts.store;
+    store.total_products = store.total_products.checked_add(1)
+        .ok_or(SupplyChainError::ArithmeticError)?;
+
+    emit!(ProductUpdatedEvent {
+        product: product.key(),
+        store: product.store,
+        price,
+        quantity,
+        updated_by: ctx.accounts.authority.key(),
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+pub fn update_product(
+    ctx: Context<UpdateProduct>,
+    price: Option<u64>,
+    quantity: Option<u64>
+) -> Result<()> {
+    validate_store_active(&ctx.accounts.store)?;
+
+    let product = &mut ctx.accounts.product;
+
+    if let Some(price) = price {
+        require!(
+            price > 0,
+            SupplyChainError::InvalidPrice
+        );
+        product.price = price;
+    }
+
+    if let Some(quantity) = quantity {
+        product.quantity = quantity;
+    }
+
+    emit!(ProductUpdatedEvent {
+        product: product.key(),
+        store: product.store,
+        price: product.price,
+        quantity: product.quantity,
+        updated_by: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Account contexts for product operations
+#[derive(Accounts)]
+pub struct AddProduct<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = has_store_role(&store, &store_member, authority.key(), STORE_ROLE_INVENTORY)
+    )]
+    pub store: Account<'info, Store>,
+    pub store_member: Option<Account<'info, StoreMember>>,
+    #[account(
+        init,
+        payer = authority,
+        space = Product::space()
+    )]
+    pub product: Account<'info, Product>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProduct<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_store_role(&store, &store_member, authority.key(), STORE_ROLE_INVENTORY)
+    )]
+    pub store: Account<'info, Store>,
+    pub store_member: Option<Account<'info, StoreMember>>,
+    #[account(
+        mut,
+        constraint = product.store == store.key()
+    )]
+    pub product: Account<'info, Product>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// Events
+#[event]
+pub struct ProductUpdatedEvent {
+    pub product: Pubkey,
+    pub store: Pubkey,
+    pub price: u64,
+    pub quantity: u64,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
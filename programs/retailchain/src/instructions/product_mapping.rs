@@ -0,0 +1,326 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::SupplyChainError;
+use super::{has_store_role, validate_supplier_verified};
+use super::supplier::SupplierProduct;
+use super::delivery::{ProofOfDelivery, PROOF_OF_DELIVERY_SEED};
+use super::purchase_order::PurchaseOrder;
+use super::store_member::{StoreMember, STORE_ROLE_INVENTORY, STORE_ROLE_RECEIVING};
+use super::config::{ProgramConfig, CONFIG_SEED};
+
+// Catalog-to-store product mapping.
+// A mapping links a store's shelf product to the supplier catalog entry that restocks
+// it, with the number of store units in one supplier unit (12 for a case of 12).
+// Mappings turn signed delivery receipts into store stock and store-unit restock
+// quantities into purchase order lines.
+pub fn set_product_mapping(
+    ctx: Context<SetProductMapping>,
+    conversion_factor: u32
+) -> Result<()> {
+    require!(
+        conversion_factor > 0,
+        SupplyChainError::InvalidQuantity
+    );
+
+    // Only live catalog entries from verified suppliers can restock store products
+    validate_supplier_verified(&ctx.accounts.supplier)?;
+    require!(
+        !ctx.accounts.supplier_product.is_discontinued,
+        SupplyChainError::ProductDiscontinued
+    );
+
+    let mapping = &mut ctx.accounts.product_mapping;
+    mapping.store = ctx.accounts.store.key();
+    mapping.product = ctx.accounts.product.key();
+    mapping.supplier = ctx.accounts.supplier_product.supplier;
+    mapping.supplier_product = ctx.accounts.supplier_product.key();
+    mapping.conversion_factor = conversion_factor;
+    mapping.set_by = ctx.accounts.authority.key();
+    mapping.bump = ctx.bumps.product_mapping;
+    mapping.version = CURRENT_ACCOUNT_VERSION;
+
+    emit!(ProductMappingUpdatedEvent {
+        product: mapping.product,
+        supplier_product: mapping.supplier_product,
+        conversion_factor: Some(conversion_factor),
+        updated_by: mapping.set_by,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn remove_product_mapping(
+    ctx: Context<RemoveProductMapping>
+) -> Result<()> {
+    let mapping = &ctx.accounts.product_mapping;
+
+    emit!(ProductMappingUpdatedEvent {
+        product: mapping.product,
+        supplier_product: mapping.supplier_product,
+        conversion_factor: None,
+        updated_by: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Adds the quantities on a shipment's signed delivery receipt to the store's
+// inventory. A verified shipment without a receipt is received at the quantities of
+// the purchase order it fulfils. Each product on the shipment is passed as a
+// (mapping, store product) pair of remaining accounts, in shipment order, with the
+// store product writable.
+pub fn receive_shipment_stock<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ReceiveShipmentStock<'info>>
+) -> Result<()> {
+    let shipment = &mut ctx.accounts.shipment;
+    let store_key = ctx.accounts.store.key();
+
+    require!(
+        shipment.status == ShipmentStatus::Delivered as u8 ||
+        shipment.status == ShipmentStatus::Verified as u8,
+        SupplyChainError::InvalidStatusTransition
+    );
+
+    require!(
+        !shipment.stock_received &&
+        ctx.accounts.proof_of_delivery.as_ref().map_or(true, |proof| !proof.stock_received),
+        SupplyChainError::DuplicateEntry
+    );
+
+    let received_quantities = match (&ctx.accounts.proof_of_delivery, &ctx.accounts.purchase_order) {
+        (Some(proof), _) => proof.received_quantities.clone(),
+        (None, Some(purchase_order)) => {
+            require!(
+                shipment.status == ShipmentStatus::Verified as u8,
+                SupplyChainError::InvalidStatusTransition
+            );
+            shipment.products.iter()
+                .map(|supplier_product| {
+                    purchase_order.lines.iter()
+                        .find(|line| line.supplier_product == *supplier_product)
+                        .map(|line| line.quantity)
+                        .ok_or(error!(SupplyChainError::OrderLinesMismatch))
+                })
+                .collect::<Result<Vec<u64>>>()?
+        },
+        (None, None) => return err!(SupplyChainError::MissingRequiredField),
+    };
+
+    require!(
+        ctx.remaining_accounts.len() == shipment.products.len() * 2,
+        SupplyChainError::MissingRequiredField
+    );
+
+    let pairs = ctx.remaining_accounts.chunks(2);
+    for ((pair, supplier_product), received) in pairs.zip(&shipment.products).zip(&received_quantities) {
+        let mapping: Account<'info, ProductMapping> = Account::try_from(&pair[0])?;
+        require!(
+            mapping.store == store_key && mapping.supplier_product == *supplier_product,
+            SupplyChainError::InvalidRelationship
+        );
+
+        let product_info = &pair[1];
+        require!(
+            product_info.is_writable && product_info.key() == mapping.product,
+            SupplyChainError::InvalidData
+        );
+
+        let mut product: Account<'info, Product> = Account::try_from(product_info)?;
+        product.quantity = product.quantity
+            .checked_add(mapping.to_store_units(*received)?)
+            .ok_or(SupplyChainError::ArithmeticError)?;
+        product.exit(&crate::ID)?;
+    }
+
+    shipment.stock_received = true;
+    if let Some(proof) = ctx.accounts.proof_of_delivery.as_mut() {
+        proof.stock_received = true;
+    }
+
+    emit!(ShipmentStockReceivedEvent {
+        shipment: shipment.key(),
+        store: store_key,
+        received_by: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Account contexts for product mapping operations
+#[derive(Accounts)]
+pub struct SetProductMapping<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_store_role(&store, &store_member, authority.key(), STORE_ROLE_INVENTORY)
+    )]
+    pub store: Account<'info, Store>,
+    pub store_member: Option<Account<'info, StoreMember>>,
+    #[account(
+        constraint = product.store == store.key()
+    )]
+    pub product: Account<'info, Product>,
+    pub supplier: Account<'info, Supplier>,
+    #[account(
+        constraint = supplier_product.supplier == supplier.key()
+    )]
+    pub supplier_product: Account<'info, SupplierProduct>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = ProductMapping::space(),
+        seeds = [PRODUCT_MAPPING_SEED, product.key().as_ref(), supplier_product.key().as_ref()],
+        bump
+    )]
+    pub product_mapping: Account<'info, ProductMapping>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveProductMapping<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_store_role(&store, &store_member, authority.key(), STORE_ROLE_INVENTORY)
+    )]
+    pub store: Account<'info, Store>,
+    pub store_member: Option<Account<'info, StoreMember>>,
+    #[account(
+        mut,
+        close = authority,
+        constraint = product_mapping.store == store.key()
+    )]
+    pub product_mapping: Account<'info, ProductMapping>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ReceiveShipmentStock<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_store_role(&store, &store_member, authority.key(), STORE_ROLE_RECEIVING)
+    )]
+    pub store: Account<'info, Store>,
+    pub store_member: Option<Account<'info, StoreMember>>,
+    #[account(
+        mut,
+        constraint = shipment.destination == store.key()
+    )]
+    pub shipment: Account<'info, ShipmentRecord>,
+    #[account(
+        mut,
+        seeds = [PROOF_OF_DELIVERY_SEED, shipment.key().as_ref()],
+        bump = proof_of_delivery.bump
+    )]
+    pub proof_of_delivery: Option<Account<'info, ProofOfDelivery>>,
+    // Supplies the quantities when a verified shipment has no proof of delivery
+    #[account(
+        constraint = shipment.purchase_order() == Some(purchase_order.key())
+    )]
+    pub purchase_order: Option<Account<'info, PurchaseOrder>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// Events
+#[event]
+pub struct ProductMappingUpdatedEvent {
+    pub product: Pubkey,
+    pub supplier_product: Pubkey,
+    // None when the mapping is removed
+    pub conversion_factor: Option<u32>,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ShipmentStockReceivedEvent {
+    pub shipment: Pubkey,
+    pub store: Pubkey,
+    pub received_by: Pubkey,
+    pub timestamp: i64,
+}
+
+// Accounts
+// Mappings are addressed by ["product_mapping", product, supplier_product]
+pub const PRODUCT_MAPPING_SEED: &[u8] = b"product_mapping";
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProductMapping {
+    pub store: Pubkey,
+    pub product: Pubkey,
+    pub supplier: Pubkey,
+    pub supplier_product: Pubkey,
+    // Store units in one supplier unit
+    pub conversion_factor: u32,
+    pub set_by: Pubkey,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl ProductMapping {
+    pub fn space() -> usize {
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+
+    pub fn to_store_units(&self, supplier_units: u64) -> Result<u64> {
+        Ok(supplier_units.checked_mul(self.conversion_factor as u64)
+            .ok_or(SupplyChainError::ArithmeticError)?)
+    }
+
+    // Supplier units needed to cover `store_units`, rounded up to whole supplier units
+    pub fn to_supplier_units(&self, store_units: u64) -> u64 {
+        store_units.div_ceil(self.conversion_factor as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping_with(conversion_factor: u32) -> ProductMapping {
+        let mut mapping = ProductMapping::deserialize(
+            &mut vec![0u8; ProductMapping::space() - 8].as_slice()
+        ).unwrap();
+        mapping.conversion_factor = conversion_factor;
+        mapping
+    }
+
+    #[test]
+    fn whole_cases_convert_exactly() {
+        let mapping = mapping_with(12);
+
+        assert_eq!(mapping.to_supplier_units(24), 2);
+        assert_eq!(mapping.to_store_units(2).unwrap(), 24);
+    }
+
+    #[test]
+    fn partial_case_rounds_up() {
+        let mapping = mapping_with(12);
+
+        assert_eq!(mapping.to_supplier_units(1), 1);
+        assert_eq!(mapping.to_supplier_units(13), 2);
+    }
+
+    #[test]
+    fn nothing_needed_orders_nothing() {
+        let mapping = mapping_with(12);
+
+        assert_eq!(mapping.to_supplier_units(0), 0);
+    }
+
+    #[test]
+    fn store_unit_overflow_is_rejected() {
+        let mapping = mapping_with(12);
+
+        assert_eq!(mapping.to_store_units(u64::MAX).err(), Some(SupplyChainError::ArithmeticError.into()));
+    }
+}
@@ -0,0 +1,519 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::SupplyChainError;
+use super::{has_store_role, has_supplier_role};
+use super::supplier::SupplierProduct;
+use super::product_mapping::ProductMapping;
+use super::pricing::{effective_unit_price, PriceSchedule, StorePriceOverride};
+use super::store_member::{StoreMember, STORE_ROLE_PURCHASING};
+use super::supplier_member::{SupplierMember, SUPPLIER_ROLE_CONTRACTS};
+use super::config::{ProgramConfig, CONFIG_SEED};
+
+// Purchase orders.
+// A lightweight alternative to supply agreements for buying straight from a
+// supplier's catalog. The store orders quantities of catalog entries at the price
+// `quote` gives them; the supplier accepts (reserving stock), ships the order through
+// `create_shipment` and invoices it. Each order line is passed as remaining accounts:
+// the catalog entry, then its price schedule and the store's price override, with
+// the program id standing in for either when there is none. Later steps take the
+// catalog entries alone, in the order of the order lines.
+pub fn create_purchase_order<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CreatePurchaseOrder<'info>>,
+    nonce: u64,
+    quantities: Vec<u64>
+) -> Result<()> {
+    require!(
+        quantities.len() * 3 == ctx.remaining_accounts.len(),
+        SupplyChainError::MissingRequiredField
+    );
+
+    let mut items: Vec<OrderItem<'info>> = Vec::new();
+    for (accounts, quantity) in ctx.remaining_accounts.chunks(3).zip(quantities) {
+        items.push(OrderItem::load(accounts, quantity)?);
+    }
+
+    let bump = ctx.bumps.purchase_order;
+    open_purchase_order(ctx.accounts, bump, nonce, items)
+}
+
+// Orders restock quantities given in store units. Each line is passed as the mapping
+// followed by the line's usual accounts; quantities are converted to supplier units
+// through the mapping and rounded up to whole supplier units.
+pub fn create_restock_order<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CreatePurchaseOrder<'info>>,
+    nonce: u64,
+    store_quantities: Vec<u64>
+) -> Result<()> {
+    let store_key = ctx.accounts.store.key();
+
+    require!(
+        store_quantities.len() * 4 == ctx.remaining_accounts.len(),
+        SupplyChainError::MissingRequiredField
+    );
+
+    let mut items: Vec<OrderItem<'info>> = Vec::new();
+    for (accounts, store_quantity) in ctx.remaining_accounts.chunks(4).zip(store_quantities) {
+        let mapping: Account<'info, ProductMapping> = Account::try_from(&accounts[0])?;
+        require!(
+            mapping.store == store_key && mapping.supplier_product == accounts[1].key(),
+            SupplyChainError::InvalidRelationship
+        );
+
+        items.push(OrderItem::load(&accounts[1..], mapping.to_supplier_units(store_quantity))?);
+    }
+
+    let bump = ctx.bumps.purchase_order;
+    open_purchase_order(ctx.accounts, bump, nonce, items)
+}
+
+// An ordered catalog entry with the pricing accounts that apply to it
+struct OrderItem<'info> {
+    product: Account<'info, SupplierProduct>,
+    price_schedule: Option<Account<'info, PriceSchedule>>,
+    price_override: Option<Account<'info, StorePriceOverride>>,
+    quantity: u64,
+}
+
+impl<'info> OrderItem<'info> {
+    // Loads a line from its (catalog entry, schedule, override) remaining accounts
+    fn load(accounts: &'info [AccountInfo<'info>], quantity: u64) -> Result<Self> {
+        Ok(OrderItem {
+            product: Account::try_from(&accounts[0])?,
+            price_schedule: optional_account(&accounts[1])?,
+            price_override: optional_account(&accounts[2])?,
+            quantity,
+        })
+    }
+}
+
+// The program id marks an absent optional account, as it does for named ones
+fn optional_account<'info, T>(info: &'info AccountInfo<'info>) -> Result<Option<Account<'info, T>>>
+where
+    T: AccountSerialize + AccountDeserialize + Owner + Clone,
+{
+    if info.key() == crate::ID {
+        return Ok(None);
+    }
+    Ok(Some(Account::try_from(info)?))
+}
+
+// Prices each ordered catalog entry as `quote` would and opens the order
+fn open_purchase_order<'info>(
+    accounts: &mut CreatePurchaseOrder<'info>,
+    bump: u8,
+    nonce: u64,
+    items: Vec<OrderItem<'info>>
+) -> Result<()> {
+    let supplier_key = accounts.supplier.key();
+    let store_key = accounts.store.key();
+    let current_time = Clock::get()?.unix_timestamp;
+
+    require!(
+        accounts.supplier.is_verified(),
+        SupplyChainError::VerificationRequired
+    );
+
+    require!(
+        !items.is_empty(),
+        SupplyChainError::MissingRequiredField
+    );
+
+    require!(
+        items.len() <= MAX_ORDER_LINES,
+        SupplyChainError::ResourceLimitExceeded
+    );
+
+    let mut lines: Vec<OrderLine> = Vec::new();
+    let mut total_amount: u64 = 0;
+
+    for item in items {
+        let product = &item.product;
+        let quantity = item.quantity;
+
+        require!(
+            product.supplier == supplier_key,
+            SupplyChainError::InvalidRelationship
+        );
+
+        require!(
+            item.price_schedule.as_ref().map_or(true, |schedule| schedule.supplier_product == product.key()) &&
+            item.price_override.as_ref().map_or(true, |price_override| {
+                price_override.supplier_product == product.key() && price_override.store == store_key
+            }),
+            SupplyChainError::InvalidRelationship
+        );
+
+        require!(
+            !product.is_discontinued,
+            SupplyChainError::ProductDiscontinued
+        );
+
+        require!(
+            quantity > 0,
+            SupplyChainError::InvalidQuantity
+        );
+
+        require!(
+            !lines.iter().any(|line| line.supplier_product == product.key()),
+            SupplyChainError::DuplicateEntry
+        );
+
+        let unit_price = effective_unit_price(
+            product,
+            item.price_schedule.as_deref(),
+            item.price_override.as_deref(),
+            quantity,
+            current_time
+        );
+        let line_amount = quantity.checked_mul(unit_price)
+            .ok_or(SupplyChainError::ArithmeticError)?;
+        total_amount = total_amount.checked_add(line_amount)
+            .ok_or(SupplyChainError::ArithmeticError)?;
+
+        lines.push(OrderLine {
+            supplier_product: product.key(),
+            quantity,
+            unit_price,
+        });
+    }
+
+    let order = &mut accounts.purchase_order;
+    order.store = store_key;
+    order.supplier = supplier_key;
+    order.created_by = accounts.authority.key();
+    order.lines = lines;
+    order.total_amount = total_amount;
+    order.status = PurchaseOrderStatus::Open as u8;
+    order.shipment = None;
+    order.created_at = current_time;
+    order.accepted_at = None;
+    order.invoiced_at = None;
+    order.payment_due_at = None;
+    order.nonce = nonce;
+    order.bump = bump;
+    order.version = CURRENT_ACCOUNT_VERSION;
+
+    emit!(PurchaseOrderCreatedEvent {
+        purchase_order: order.key(),
+        store: order.store,
+        supplier: order.supplier,
+        total_amount,
+        created_by: order.created_by,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+// Accepting reserves the ordered quantities from the catalog
+pub fn accept_purchase_order<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SupplierPurchaseOrderAction<'info>>
+) -> Result<()> {
+    let order = &mut ctx.accounts.purchase_order;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    require!(
+        order.status == PurchaseOrderStatus::Open as u8,
+        SupplyChainError::InvalidPurchaseOrderStatus
+    );
+
+    adjust_reserved_stock(&order.lines, ctx.remaining_accounts, StockMovement::Reserve)?;
+
+    let old_status = order.status;
+    order.status = PurchaseOrderStatus::Accepted as u8;
+    order.accepted_at = Some(current_time);
+
+    emit!(PurchaseOrderStatusUpdatedEvent {
+        purchase_order: order.key(),
+        old_status,
+        new_status: order.status,
+        updated_by: ctx.accounts.authority.key(),
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+pub fn reject_purchase_order(
+    ctx: Context<SupplierPurchaseOrderAction>
+) -> Result<()> {
+    let order = &mut ctx.accounts.purchase_order;
+
+    require!(
+        order.status == PurchaseOrderStatus::Open as u8,
+        SupplyChainError::InvalidPurchaseOrderStatus
+    );
+
+    let old_status = order.status;
+    order.status = PurchaseOrderStatus::Rejected as u8;
+
+    emit!(PurchaseOrderStatusUpdatedEvent {
+        purchase_order: order.key(),
+        old_status,
+        new_status: order.status,
+        updated_by: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Either side can cancel before the order ships; an accepted order releases its
+// reserved stock back to the catalog
+pub fn cancel_purchase_order<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CancelPurchaseOrder<'info>>
+) -> Result<()> {
+    let order = &mut ctx.accounts.purchase_order;
+
+    require!(
+        order.status == PurchaseOrderStatus::Open as u8 ||
+        order.status == PurchaseOrderStatus::Accepted as u8,
+        SupplyChainError::InvalidPurchaseOrderStatus
+    );
+
+    if order.status == PurchaseOrderStatus::Accepted as u8 {
+        adjust_reserved_stock(&order.lines, ctx.remaining_accounts, StockMovement::Release)?;
+    }
+
+    let old_status = order.status;
+    order.status = PurchaseOrderStatus::Canceled as u8;
+
+    emit!(PurchaseOrderStatusUpdatedEvent {
+        purchase_order: order.key(),
+        old_status,
+        new_status: order.status,
+        updated_by: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Invoicing settles the order's stock reservations on its catalog entries
+pub fn invoice_purchase_order<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SupplierPurchaseOrderAction<'info>>,
+    payment_due_at: i64
+) -> Result<()> {
+    let order = &mut ctx.accounts.purchase_order;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    require!(
+        order.status == PurchaseOrderStatus::Shipped as u8,
+        SupplyChainError::InvalidPurchaseOrderStatus
+    );
+
+    require!(
+        payment_due_at > current_time,
+        SupplyChainError::InvalidDeadline
+    );
+
+    adjust_reserved_stock(&order.lines, ctx.remaining_accounts, StockMovement::Fulfil)?;
+
+    order.status = PurchaseOrderStatus::Invoiced as u8;
+    order.invoiced_at = Some(current_time);
+    order.payment_due_at = Some(payment_due_at);
+
+    emit!(PurchaseOrderInvoicedEvent {
+        purchase_order: order.key(),
+        store: order.store,
+        supplier: order.supplier,
+        amount: order.total_amount,
+        payment_due_at,
+        invoiced_by: ctx.accounts.authority.key(),
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+// How an order's quantities move through its catalog entries
+#[derive(Clone, Copy)]
+enum StockMovement {
+    // Taken out of available stock and held for the order
+    Reserve,
+    // Handed back to available stock
+    Release,
+    // Held stock that has left with the order
+    Fulfil,
+}
+
+// Moves the ordered quantities on the catalog entries, which are passed writable in
+// order-line order
+fn adjust_reserved_stock<'info>(
+    lines: &[OrderLine],
+    product_infos: &'info [AccountInfo<'info>],
+    movement: StockMovement,
+) -> Result<()> {
+    require!(
+        product_infos.len() == lines.len(),
+        SupplyChainError::MissingRequiredField
+    );
+
+    for (product_info, line) in product_infos.iter().zip(lines) {
+        require!(
+            product_info.is_writable && product_info.key() == line.supplier_product,
+            SupplyChainError::InvalidData
+        );
+
+        let mut product: Account<'info, SupplierProduct> = Account::try_from(product_info)?;
+        match movement {
+            StockMovement::Reserve => {
+                product.available_quantity = product.available_quantity.checked_sub(line.quantity)
+                    .ok_or(SupplyChainError::InsufficientStock)?;
+                product.reserved_quantity = product.reserved_quantity.checked_add(line.quantity)
+                    .ok_or(SupplyChainError::ArithmeticError)?;
+            },
+            StockMovement::Release => {
+                product.available_quantity = product.available_quantity.checked_add(line.quantity)
+                    .ok_or(SupplyChainError::ArithmeticError)?;
+                product.reserved_quantity = product.reserved_quantity.checked_sub(line.quantity)
+                    .ok_or(SupplyChainError::ArithmeticError)?;
+            },
+            StockMovement::Fulfil => {
+                product.reserved_quantity = product.reserved_quantity.checked_sub(line.quantity)
+                    .ok_or(SupplyChainError::ArithmeticError)?;
+            },
+        }
+        product.exit(&crate::ID)?;
+    }
+
+    Ok(())
+}
+
+// Account contexts for purchase order operations
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct CreatePurchaseOrder<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_store_role(&store, &store_member, authority.key(), STORE_ROLE_PURCHASING)
+    )]
+    pub store: Account<'info, Store>,
+    pub store_member: Option<Account<'info, StoreMember>>,
+    pub supplier: Account<'info, Supplier>,
+    #[account(
+        init,
+        payer = authority,
+        space = PurchaseOrder::space(),
+        seeds = [PURCHASE_ORDER_SEED, store.key().as_ref(), supplier.key().as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub purchase_order: Account<'info, PurchaseOrder>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SupplierPurchaseOrderAction<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_CONTRACTS)
+    )]
+    pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    #[account(
+        mut,
+        constraint = purchase_order.supplier == supplier.key()
+    )]
+    pub purchase_order: Account<'info, PurchaseOrder>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CancelPurchaseOrder<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = purchase_order.store == store.key() && purchase_order.supplier == supplier.key(),
+        constraint = has_store_role(&store, &store_member, authority.key(), STORE_ROLE_PURCHASING) ||
+                    has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_CONTRACTS)
+    )]
+    pub purchase_order: Account<'info, PurchaseOrder>,
+    pub store: Account<'info, Store>,
+    pub store_member: Option<Account<'info, StoreMember>>,
+    pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// Events
+#[event]
+pub struct PurchaseOrderCreatedEvent {
+    pub purchase_order: Pubkey,
+    pub store: Pubkey,
+    pub supplier: Pubkey,
+    pub total_amount: u64,
+    pub created_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PurchaseOrderStatusUpdatedEvent {
+    pub purchase_order: Pubkey,
+    pub old_status: u8,
+    pub new_status: u8,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PurchaseOrderInvoicedEvent {
+    pub purchase_order: Pubkey,
+    pub store: Pubkey,
+    pub supplier: Pubkey,
+    pub amount: u64,
+    pub payment_due_at: i64,
+    pub invoiced_by: Pubkey,
+    pub timestamp: i64,
+}
+
+// Accounts
+// Purchase orders are addressed by ["purchase_order", store, supplier, nonce]
+pub const PURCHASE_ORDER_SEED: &[u8] = b"purchase_order";
+
+pub const MAX_ORDER_LINES: usize = 8;
+
+// Quantity of one catalog entry at the price it was ordered at
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct OrderLine {
+    pub supplier_product: Pubkey,
+    pub quantity: u64,
+    pub unit_price: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PurchaseOrder {
+    pub store: Pubkey,
+    pub supplier: Pubkey,
+    pub created_by: Pubkey,
+    #[max_len(MAX_ORDER_LINES)]
+    pub lines: Vec<OrderLine>,
+    pub total_amount: u64,
+    pub status: u8,
+    pub shipment: Option<Pubkey>,
+    pub created_at: i64,
+    pub accepted_at: Option<i64>,
+    pub invoiced_at: Option<i64>,
+    pub payment_due_at: Option<i64>,
+    pub nonce: u64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl PurchaseOrder {
+    pub fn space() -> usize {
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+
+    // True if `products` lists each ordered catalog entry once and nothing else
+    pub fn matches_products(&self, products: &[Pubkey]) -> bool {
+        products.len() == self.lines.len() &&
+            self.lines.iter().all(|line| products.contains(&line.supplier_product))
+    }
+}
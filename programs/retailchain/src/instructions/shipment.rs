@@ -1,7 +1,13 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::errors::SupplyChainError;
-use super::{validate_supplier_verified, validate_verifier_credentials};
+use super::{validate_supplier_in_good_standing, validate_supply_chain_relationship, validate_string_length, has_store_role, has_supplier_role};
+use super::store_member::{StoreMember, STORE_ROLE_RECEIVING, STORE_ROLE_PURCHASING};
+use super::config::{ProgramConfig, CONFIG_SEED};
+use super::supplier_member::{SupplierMember, SUPPLIER_ROLE_SHIPPING};
+use super::certification::{validate_certification, Certification};
+use super::supplier::SupplierReverification;
+use super::purchase_order::{PurchaseOrder, PurchaseOrderStatusUpdatedEvent};
 
 // Shipment creation and management
 pub fn create_shipment(
@@ -14,11 +20,19 @@ pub fn create_shipment(
 ) -> Result<()> {
     let shipment = &mut ctx.accounts.shipment;
     
-    // Validate tracking ID format (example validation)
+    // Validate tracking ID and location lengths
     require!(
-        !tracking_id.is_empty() && tracking_id.len() <= 32,
+        !tracking_id.is_empty(),
         SupplyChainError::InvalidData
     );
+    validate_string_length(&tracking_id, MAX_TRACKING_ID_LEN)?;
+    validate_string_length(&origin_location, MAX_LOCATION_LEN)?;
+    validate_string_length(&destination_location, MAX_LOCATION_LEN)?;
+    
+    require!(
+        products.len() <= MAX_SHIPMENT_PRODUCTS,
+        SupplyChainError::ResourceLimitExceeded
+    );
     
     // Validate estimated arrival time is in the future
     let current_time = Clock::get()?.unix_timestamp;
@@ -27,17 +41,74 @@ pub fn create_shipment(
         SupplyChainError::InvalidData
     );
     
+    // Suppliers awaiting re-verification can keep shipping until their grace period ends
+    validate_supplier_in_good_standing(&ctx.accounts.supplier, &ctx.accounts.reverification, current_time)?;
+    
     // Initialize the shipment record
-    shipment.tracking_id = tracking_id;
-    shipment.supplier = ctx.accounts.supplier.key();
-    shipment.destination = ctx.accounts.store.key();
-    shipment.origin_location = origin_location;
-    shipment.destination_location = destination_location;
-    shipment.created_at = current_time;
-    shipment.estimated_arrival = estimated_arrival;
-    shipment.status = ShipmentStatus::Created as u8;
-    shipment.products = products;
-    shipment.verified_by = Vec::new();
+    shipment.initialize(
+        tracking_id,
+        ctx.accounts.supplier.key(),
+        ctx.accounts.store.key(),
+        origin_location,
+        destination_location,
+        estimated_arrival,
+        products,
+        current_time,
+    );
+    shipment.bump = ctx.bumps.shipment;
+    shipment.version = CURRENT_ACCOUNT_VERSION;
+    
+    // Claim the tracking ID for this shipment
+    ctx.accounts.tracking_id_record.claim(
+        &shipment.tracking_id,
+        shipment.key(),
+        shipment.supplier,
+        ctx.bumps.tracking_id_record,
+        current_time,
+    );
+    
+    // Link the shipment to the agreement it fulfils, if any
+    if let Some(agreement) = &ctx.accounts.agreement {
+        validate_supply_chain_relationship(&ctx.accounts.supplier, &ctx.accounts.store, agreement)?;
+        
+        // Agreements that need a certification can only ship while it is valid
+        if let Some(cert_type) = agreement.required_certification() {
+            validate_certification(&ctx.accounts.certification, ctx.accounts.supplier.key(), cert_type, current_time)?;
+        }
+        
+        shipment.agreement = Some(agreement.key());
+    }
+    
+    // Ship an accepted purchase order, if this shipment fulfils one
+    if let Some(purchase_order) = ctx.accounts.purchase_order.as_mut() {
+        require!(
+            purchase_order.supplier == ctx.accounts.supplier.key() &&
+            purchase_order.store == ctx.accounts.store.key(),
+            SupplyChainError::InvalidRelationship
+        );
+        require!(
+            purchase_order.status == PurchaseOrderStatus::Accepted as u8,
+            SupplyChainError::InvalidPurchaseOrderStatus
+        );
+        // The shipment must carry exactly the catalog entries that were ordered
+        require!(
+            purchase_order.matches_products(&shipment.products),
+            SupplyChainError::OrderLinesMismatch
+        );
+        
+        let old_status = purchase_order.status;
+        purchase_order.status = PurchaseOrderStatus::Shipped as u8;
+        purchase_order.shipment = Some(shipment.key());
+        shipment.set_purchase_order(Some(purchase_order.key()));
+        
+        emit!(PurchaseOrderStatusUpdatedEvent {
+            purchase_order: purchase_order.key(),
+            old_status,
+            new_status: purchase_order.status,
+            updated_by: ctx.accounts.authority.key(),
+            timestamp: current_time,
+        });
+    }
     
     // Record the creation event
     emit!(ShipmentCreatedEvent {
@@ -65,6 +136,27 @@ pub fn update_shipment_status(
         SupplyChainError::InvalidStatusTransition
     );
     
+    // Cancellations, returns and losses go through their dedicated instructions
+    require!(
+        new_status != ShipmentStatus::Canceled as u8 &&
+        new_status != ShipmentStatus::ReturnInTransit as u8 &&
+        new_status != ShipmentStatus::Returned as u8 &&
+        new_status != ShipmentStatus::Lost as u8,
+        SupplyChainError::InvalidStatusTransition
+    );
+    
+    // Fully split and consolidated shipments take their status from their children
+    require!(
+        !shipment.is_rolled_up(),
+        SupplyChainError::ShipmentStatusRolledUp
+    );
+    
+    // Recorded exceptions must be resolved before the shipment can move on
+    require!(
+        shipment.open_exceptions == 0,
+        SupplyChainError::UnresolvedExceptions
+    );
+    
     // Update status
     let old_status = shipment.status;
     shipment.status = new_status;
@@ -74,9 +166,28 @@ pub fn update_shipment_status(
         shipment.verified_by.push(ctx.accounts.authority.key());
     }
     
-    // If transitioning to Delivered status, verify products received
-    if new_status == ShipmentStatus::Delivered as u8 {
-        // Any additional verification logic here
+    // Deliveries count toward the supplier's on-time record
+    if new_status == ShipmentStatus::Delivered as u8 && shipment.counts_toward_supplier_metrics() {
+        ctx.accounts.supplier.record_delivery(current_time <= shipment.committed_eta())?;
+    }
+    
+    // The original shipment only counts as returned once its return reaches the supplier
+    if new_status == ShipmentStatus::Delivered as u8 && shipment.is_return {
+        let original = ctx.accounts.linked_shipment.as_mut()
+            .ok_or(SupplyChainError::MissingRequiredField)?;
+        require!(
+            is_valid_status_transition(original.status, ShipmentStatus::Returned as u8),
+            SupplyChainError::InvalidStatusTransition
+        );
+        original.status = ShipmentStatus::Returned as u8;
+        
+        emit!(ShipmentStatusUpdatedEvent {
+            shipment: original.key(),
+            old_status: ShipmentStatus::ReturnInTransit as u8,
+            new_status: ShipmentStatus::Returned as u8,
+            updated_by: ctx.accounts.authority.key(),
+            timestamp: current_time,
+        });
     }
     
     // Record the status update event
@@ -119,11 +230,12 @@ pub fn verify_shipment_delivery(
     });
     
     // If this verification completes an agreement, update the agreement status
-    if let Some(agreement) = &ctx.accounts.agreement {
+    if let Some(agreement) = ctx.accounts.agreement.as_mut() {
         if agreement.status == AgreementStatus::Active as u8 {
             // Logic to check if all shipments for this agreement are verified
             // For simplicity, we're assuming one shipment per agreement here
-            ctx.accounts.agreement.as_mut().unwrap().status = AgreementStatus::Completed as u8;
+            agreement.status = AgreementStatus::Completed as u8;
+            ctx.accounts.supplier.record_agreement_completed()?;
             
             emit!(AgreementCompletedEvent {
                 agreement: agreement.key(),
@@ -136,10 +248,13 @@ pub fn verify_shipment_delivery(
 }
 
 pub fn add_shipment_exception(
-    ctx: Context<UpdateShipment>,
+    ctx: Context<AddShipmentException>,
+    reason: u8,
+    severity: u8,
     exception_details: String
 ) -> Result<()> {
     let shipment = &mut ctx.accounts.shipment;
+    let exception = &mut ctx.accounts.exception;
     let current_time = Clock::get()?.unix_timestamp;
     
     // Can only add exceptions to shipments in transit or already in exception state
@@ -149,42 +264,454 @@ pub fn add_shipment_exception(
         SupplyChainError::InvalidStatusTransition
     );
     
-    // Set shipment to exception status
-    shipment.status = ShipmentStatus::Exception as u8;
+    require!(
+        reason <= ExceptionReason::RouteDeviation as u8 &&
+        severity <= ExceptionSeverity::Critical as u8,
+        SupplyChainError::InvalidData
+    );
+    
+    validate_string_length(&exception_details, MAX_NOTES_LEN)?;
+    
+    raise_shipment_exception(
+        shipment,
+        exception,
+        &mut ctx.accounts.supplier,
+        reason,
+        severity,
+        exception_details,
+        ctx.accounts.authority.key(),
+        ctx.bumps.exception,
+        current_time,
+    )?;
     
     // Record the authority that reported the exception
     if !shipment.verified_by.contains(&ctx.accounts.authority.key()) {
         shipment.verified_by.push(ctx.accounts.authority.key());
     }
     
-    // Record exception event
+    Ok(())
+}
+
+// Creates the next exception record for a shipment and moves it into Exception status.
+// Manual reports, the overdue crank and route checks all raise exceptions through here.
+#[allow(clippy::too_many_arguments)]
+fn raise_shipment_exception(
+    shipment: &mut Account<ShipmentRecord>,
+    exception: &mut Account<ShipmentException>,
+    supplier: &mut Account<Supplier>,
+    reason: u8,
+    severity: u8,
+    details: String,
+    raised_by: Pubkey,
+    bump: u8,
+    current_time: i64,
+) -> Result<()> {
+    exception.shipment = shipment.key();
+    exception.index = shipment.exception_count;
+    exception.reason = reason;
+    exception.severity = severity;
+    exception.details = details.clone();
+    exception.raised_by = raised_by;
+    exception.raised_at = current_time;
+    exception.resolved = false;
+    exception.resolved_by = None;
+    exception.remedy = None;
+    exception.resolved_at = None;
+    exception.bump = bump;
+    exception.version = CURRENT_ACCOUNT_VERSION;
+    
+    shipment.status = ShipmentStatus::Exception as u8;
+    shipment.exception_count = shipment.exception_count.checked_add(1)
+        .ok_or(SupplyChainError::ArithmeticError)?;
+    shipment.open_exceptions = shipment.open_exceptions.checked_add(1)
+        .ok_or(SupplyChainError::ArithmeticError)?;
+    
+    if shipment.counts_toward_supplier_metrics() {
+        supplier.record_exception()?;
+    }
+    
     emit!(ShipmentExceptionEvent {
         shipment: shipment.key(),
-        reported_by: ctx.accounts.authority.key(),
-        details: exception_details,
+        reported_by: raised_by,
+        details,
+        timestamp: current_time,
+    });
+    
+    Ok(())
+}
+
+pub fn resolve_exception(
+    ctx: Context<ResolveException>,
+    remedy: String,
+    resolution_outcome: u8
+) -> Result<()> {
+    let shipment = &mut ctx.accounts.shipment;
+    let exception = &mut ctx.accounts.exception;
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    require!(
+        !exception.resolved,
+        SupplyChainError::ExceptionAlreadyResolved
+    );
+    
+    validate_string_length(&remedy, MAX_NOTES_LEN)?;
+    
+    // A shipment resolved as lost is terminal, but its other open exceptions
+    // still have to be closed
+    let is_lost = shipment.status == ShipmentStatus::Lost as u8;
+    require!(
+        shipment.status == ShipmentStatus::Exception as u8 || is_lost,
+        SupplyChainError::InvalidStatusTransition
+    );
+    
+    // Update exception record
+    exception.resolved = true;
+    exception.resolved_by = Some(ctx.accounts.authority.key());
+    exception.remedy = Some(remedy);
+    exception.resolved_at = Some(current_time);
+    
+    shipment.open_exceptions = shipment.open_exceptions.checked_sub(1)
+        .ok_or(SupplyChainError::ArithmeticError)?;
+    
+    // Update shipment based on resolution outcome
+    let old_status = shipment.status;
+    match resolution_outcome {
+        0 => {
+            // Resume the shipment once nothing else is outstanding
+            if !is_lost && shipment.open_exceptions == 0 {
+                shipment.status = ShipmentStatus::InTransit as u8;
+            }
+        },
+        1 => {
+            // Terminate the shipment as lost
+            shipment.status = ShipmentStatus::Lost as u8;
+        },
+        _ => return Err(SupplyChainError::InvalidData.into())
+    }
+    
+    emit!(ShipmentExceptionResolvedEvent {
+        shipment: shipment.key(),
+        exception: exception.key(),
+        resolved_by: ctx.accounts.authority.key(),
+        outcome: resolution_outcome,
+        timestamp: current_time,
+    });
+    
+    if shipment.status != old_status {
+        emit!(ShipmentStatusUpdatedEvent {
+            shipment: shipment.key(),
+            old_status,
+            new_status: shipment.status,
+            updated_by: ctx.accounts.authority.key(),
+            timestamp: current_time,
+        });
+    }
+    
+    Ok(())
+}
+
+pub fn cancel_shipment(
+    ctx: Context<CancelShipment>
+) -> Result<()> {
+    let shipment = &mut ctx.accounts.shipment;
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    // Shipments can only be canceled before pickup
+    require!(
+        is_valid_status_transition(shipment.status, ShipmentStatus::Canceled as u8),
+        SupplyChainError::InvalidStatusTransition
+    );
+    
+    let old_status = shipment.status;
+    shipment.status = ShipmentStatus::Canceled as u8;
+    
+    // A purchase order shipped by this shipment goes back to accepted, keeping its
+    // stock reserved, so it can be shipped again or canceled
+    if shipment.purchase_order().is_some() {
+        let purchase_order = ctx.accounts.purchase_order.as_mut()
+            .ok_or(SupplyChainError::MissingRequiredField)?;
+        
+        if purchase_order.status == PurchaseOrderStatus::Shipped as u8 &&
+            purchase_order.shipment == Some(shipment.key()) {
+            let old_order_status = purchase_order.status;
+            purchase_order.status = PurchaseOrderStatus::Accepted as u8;
+            purchase_order.shipment = None;
+            
+            emit!(PurchaseOrderStatusUpdatedEvent {
+                purchase_order: purchase_order.key(),
+                old_status: old_order_status,
+                new_status: purchase_order.status,
+                updated_by: ctx.accounts.authority.key(),
+                timestamp: current_time,
+            });
+        }
+    }
+    
+    emit!(ShipmentStatusUpdatedEvent {
+        shipment: shipment.key(),
+        old_status,
+        new_status: ShipmentStatus::Canceled as u8,
+        updated_by: ctx.accounts.authority.key(),
+        timestamp: current_time,
+    });
+    
+    Ok(())
+}
+
+pub fn initiate_return(
+    ctx: Context<InitiateReturn>,
+    tracking_id: String,
+    estimated_arrival: i64
+) -> Result<()> {
+    let original = &mut ctx.accounts.original_shipment;
+    let return_shipment = &mut ctx.accounts.return_shipment;
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    // Only delivered goods that have not been returned already can be sent back
+    require!(
+        is_valid_status_transition(original.status, ShipmentStatus::ReturnInTransit as u8),
+        SupplyChainError::InvalidStatusTransition
+    );
+    
+    require!(
+        !original.is_return && original.linked_shipment.is_none(),
+        SupplyChainError::DuplicateEntry
+    );
+    
+    require!(
+        !tracking_id.is_empty(),
+        SupplyChainError::InvalidData
+    );
+    validate_string_length(&tracking_id, MAX_TRACKING_ID_LEN)?;
+    
+    require!(
+        estimated_arrival > current_time,
+        SupplyChainError::InvalidData
+    );
+    
+    // The reverse shipment travels from the store back to the supplier
+    return_shipment.initialize(
+        tracking_id,
+        original.supplier,
+        original.supplier,
+        original.destination_location.clone(),
+        original.origin_location.clone(),
+        estimated_arrival,
+        original.products.clone(),
+        current_time,
+    );
+    return_shipment.bump = ctx.bumps.return_shipment;
+    return_shipment.version = CURRENT_ACCOUNT_VERSION;
+    ctx.accounts.tracking_id_record.claim(
+        &return_shipment.tracking_id,
+        return_shipment.key(),
+        return_shipment.supplier,
+        ctx.bumps.tracking_id_record,
+        current_time,
+    );
+    return_shipment.linked_shipment = Some(original.key());
+    return_shipment.is_return = true;
+    
+    // Link the original shipment to its return
+    let old_status = original.status;
+    original.status = ShipmentStatus::ReturnInTransit as u8;
+    original.linked_shipment = Some(return_shipment.key());
+    
+    emit!(ShipmentCreatedEvent {
+        shipment: return_shipment.key(),
+        supplier: return_shipment.supplier,
+        destination: return_shipment.destination,
+        tracking_id: return_shipment.tracking_id.clone(),
+        estimated_arrival,
+        timestamp: current_time,
+    });
+    
+    emit!(ShipmentStatusUpdatedEvent {
+        shipment: original.key(),
+        old_status,
+        new_status: ShipmentStatus::ReturnInTransit as u8,
+        updated_by: ctx.accounts.authority.key(),
+        timestamp: current_time,
+    });
+    
+    emit!(ShipmentReturnInitiatedEvent {
+        original_shipment: original.key(),
+        return_shipment: return_shipment.key(),
+        initiated_by: ctx.accounts.authority.key(),
+        timestamp: current_time,
+    });
+    
+    Ok(())
+}
+
+pub fn revise_eta(
+    ctx: Context<ReviseEta>,
+    new_eta: i64,
+    reason_code: u8
+) -> Result<()> {
+    let shipment = &mut ctx.accounts.shipment;
+    let eta_revision = &mut ctx.accounts.eta_revision;
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    // ETAs can only be revised while the shipment is still on its way
+    require!(
+        shipment.status == ShipmentStatus::Created as u8 ||
+        shipment.status == ShipmentStatus::InTransit as u8 ||
+        shipment.status == ShipmentStatus::Exception as u8,
+        SupplyChainError::InvalidStatusTransition
+    );
+    
+    require!(
+        new_eta > current_time && new_eta != shipment.estimated_arrival,
+        SupplyChainError::InvalidTimestamp
+    );
+    
+    require!(
+        reason_code <= EtaReasonCode::Other as u8,
+        SupplyChainError::InvalidData
+    );
+    
+    // Keep a record of the revision
+    let previous_eta = shipment.estimated_arrival;
+    eta_revision.shipment = shipment.key();
+    eta_revision.revision = shipment.eta_revision_count;
+    eta_revision.previous_eta = previous_eta;
+    eta_revision.new_eta = new_eta;
+    eta_revision.reason_code = reason_code;
+    eta_revision.revised_by = ctx.accounts.authority.key();
+    eta_revision.timestamp = current_time;
+    eta_revision.bump = ctx.bumps.eta_revision;
+    eta_revision.version = CURRENT_ACCOUNT_VERSION;
+    
+    // Update the shipment
+    shipment.estimated_arrival = new_eta;
+    shipment.eta_revision_count = shipment.eta_revision_count.checked_add(1)
+        .ok_or(SupplyChainError::ArithmeticError)?;
+    
+    emit!(EtaRevisedEvent {
+        shipment: shipment.key(),
+        revision: eta_revision.revision,
+        previous_eta,
+        new_eta,
+        reason_code,
+        revised_by: ctx.accounts.authority.key(),
+        timestamp: current_time,
+    });
+    
+    Ok(())
+}
+
+// The supplier assigns the credentialed carrier moving the shipment; reassigning
+// replaces the previous carrier
+pub fn assign_shipment_carrier(
+    ctx: Context<AssignShipmentCarrier>
+) -> Result<()> {
+    let shipment = &ctx.accounts.shipment;
+    let assignment = &mut ctx.accounts.carrier_assignment;
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    require!(
+        shipment.status == ShipmentStatus::Created as u8 ||
+        shipment.status == ShipmentStatus::InTransit as u8 ||
+        shipment.status == ShipmentStatus::Exception as u8,
+        SupplyChainError::InvalidStatusTransition
+    );
+    
+    assignment.shipment = shipment.key();
+    assignment.carrier = ctx.accounts.carrier_credentials.authority;
+    assignment.assigned_by = ctx.accounts.authority.key();
+    assignment.assigned_at = current_time;
+    assignment.bump = ctx.bumps.carrier_assignment;
+    assignment.version = CURRENT_ACCOUNT_VERSION;
+    
+    emit!(ShipmentCarrierAssignedEvent {
+        shipment: shipment.key(),
+        carrier: assignment.carrier,
+        assigned_by: assignment.assigned_by,
         timestamp: current_time,
     });
     
     Ok(())
 }
 
+// Permissionless crank: anyone can flag an in-transit shipment that is past its ETA
+pub fn flag_overdue_shipment(
+    ctx: Context<FlagOverdueShipment>
+) -> Result<()> {
+    let shipment = &mut ctx.accounts.shipment;
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    require!(
+        shipment.status == ShipmentStatus::InTransit as u8,
+        SupplyChainError::InvalidStatusTransition
+    );
+    
+    require!(
+        current_time > shipment.estimated_arrival,
+        SupplyChainError::ShipmentNotOverdue
+    );
+    
+    let details = format!(
+        "Overdue: estimated arrival {} passed by {}s",
+        shipment.estimated_arrival,
+        current_time - shipment.estimated_arrival
+    );
+    raise_shipment_exception(
+        shipment,
+        &mut ctx.accounts.exception,
+        &mut ctx.accounts.supplier,
+        ExceptionReason::Delay as u8,
+        ExceptionSeverity::Medium as u8,
+        details,
+        ctx.accounts.cranker.key(),
+        ctx.bumps.exception,
+        current_time,
+    )?;
+    
+    Ok(())
+}
+
 // Account contexts for shipment operations
 #[derive(Accounts)]
+#[instruction(tracking_id: String)]
 pub struct CreateShipment<'info> {
-    #[account(mut)]
+    // Longer values cannot be used as PDA seeds
+    #[account(
+        mut,
+        constraint = tracking_id.len() <= MAX_TRACKING_ID_LEN @ SupplyChainError::StringTooLong
+    )]
     pub authority: Signer<'info>,
     #[account(
-        constraint = supplier.key == authority.key(),
-        constraint = supplier.is_verified
+        constraint = has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_SHIPPING)
     )]
     pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    pub reverification: Option<Account<'info, SupplierReverification>>,
     pub store: Account<'info, Store>,
+    // Global tracking ID registry entry; an already claimed ID fails here
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = TrackingIdRecord::space(),
+        seeds = [TRACKING_ID_SEED, tracking_id.as_bytes()],
+        bump,
+        constraint = !tracking_id_record.is_claimed() @ SupplyChainError::DuplicateEntry
+    )]
+    pub tracking_id_record: Account<'info, TrackingIdRecord>,
     #[account(
         init,
         payer = authority,
-        space = ShipmentRecord::space()
+        space = ShipmentRecord::space(),
+        seeds = [SHIPMENT_SEED, supplier.key().as_ref(), tracking_id.as_bytes()],
+        bump
     )]
     pub shipment: Account<'info, ShipmentRecord>,
+    pub agreement: Option<Account<'info, SupplyAgreement>>,
+    pub certification: Option<Account<'info, Certification>>,
+    #[account(mut)]
+    pub purchase_order: Option<Account<'info, PurchaseOrder>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
     pub system_program: Program<'info, System>,
 }
 
@@ -194,16 +721,96 @@ pub struct UpdateShipment<'info> {
     pub authority: Signer<'info>,
     #[account(
         mut,
-        constraint = (shipment.supplier == supplier.key() && supplier.key() == authority.key()) || 
-                    (shipment.destination == store.key() && store.owner == authority.key()) ||
+        constraint = (shipment.supplier == supplier.key() && has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_SHIPPING)) || 
+                    (shipment.destination == store.key() &&
+                     has_store_role(&store, &store_member, authority.key(), STORE_ROLE_RECEIVING)) ||
                     (authority_credentials.is_some() && 
                      authority_credentials.as_ref().unwrap().authority == authority.key() &&
                      authority_credentials.as_ref().unwrap().is_verifier)
     )]
     pub shipment: Account<'info, ShipmentRecord>,
+    #[account(
+        mut,
+        constraint = shipment.supplier == supplier.key()
+    )]
+    pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    pub store: Account<'info, Store>,
+    pub store_member: Option<Account<'info, StoreMember>>,
+    pub authority_credentials: Option<Account<'info, VerifierCredential>>,
+    // The original shipment, required when a return is marked delivered
+    #[account(
+        mut,
+        constraint = shipment.linked_shipment == Some(linked_shipment.key())
+    )]
+    pub linked_shipment: Option<Account<'info, ShipmentRecord>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AddShipmentException<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = (shipment.supplier == supplier.key() && has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_SHIPPING)) ||
+                    (shipment.destination == store.key() &&
+                     has_store_role(&store, &store_member, authority.key(), STORE_ROLE_RECEIVING)) ||
+                    (authority_credentials.is_some() &&
+                     authority_credentials.as_ref().unwrap().authority == authority.key() &&
+                     authority_credentials.as_ref().unwrap().is_verifier)
+    )]
+    pub shipment: Account<'info, ShipmentRecord>,
+    #[account(
+        mut,
+        constraint = shipment.supplier == supplier.key()
+    )]
     pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
     pub store: Account<'info, Store>,
+    pub store_member: Option<Account<'info, StoreMember>>,
+    #[account(
+        init,
+        payer = authority,
+        space = ShipmentException::space(),
+        seeds = [
+            SHIPMENT_EXCEPTION_SEED,
+            shipment.key().as_ref(),
+            &shipment.exception_count.to_le_bytes()
+        ],
+        bump
+    )]
+    pub exception: Account<'info, ShipmentException>,
+    pub authority_credentials: Option<Account<'info, VerifierCredential>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveException<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    // Either the supplier or a credentialed verifier can resolve an exception
+    #[account(
+        mut,
+        constraint = (shipment.supplier == supplier.key() && has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_SHIPPING)) ||
+                    (authority_credentials.is_some() &&
+                     authority_credentials.as_ref().unwrap().authority == authority.key() &&
+                     authority_credentials.as_ref().unwrap().is_verifier)
+    )]
+    pub shipment: Account<'info, ShipmentRecord>,
+    pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    #[account(
+        mut,
+        constraint = exception.shipment == shipment.key()
+    )]
+    pub exception: Account<'info, ShipmentException>,
     pub authority_credentials: Option<Account<'info, VerifierCredential>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
 }
 
 #[derive(Accounts)]
@@ -212,13 +819,188 @@ pub struct VerifyShipmentDelivery<'info> {
     pub authority: Signer<'info>,
     #[account(
         mut,
-        constraint = shipment.destination == store.key() && store.owner == authority.key(),
+        constraint = shipment.destination == store.key() &&
+                    has_store_role(&store, &store_member, authority.key(), STORE_ROLE_RECEIVING),
         constraint = shipment.status == ShipmentStatus::Delivered as u8
     )]
     pub shipment: Account<'info, ShipmentRecord>,
     pub store: Account<'info, Store>,
+    pub store_member: Option<Account<'info, StoreMember>>,
+    #[account(
+        mut,
+        constraint = agreement.as_ref().map_or(true, |agreement| agreement.supplier == supplier.key())
+    )]
     pub supplier: Account<'info, Supplier>,
+    // Only the agreement this shipment was created against can be completed by its delivery
+    #[account(
+        mut,
+        constraint = shipment.agreement == Some(agreement.key()),
+        constraint = agreement.store == store.key(),
+        constraint = shipment.supplier == agreement.supplier
+    )]
     pub agreement: Option<Account<'info, SupplyAgreement>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CancelShipment<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = (shipment.supplier == supplier.key() && has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_SHIPPING)) ||
+                    (shipment.destination == store.key() &&
+                     has_store_role(&store, &store_member, authority.key(), STORE_ROLE_PURCHASING))
+    )]
+    pub shipment: Account<'info, ShipmentRecord>,
+    pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    pub store: Account<'info, Store>,
+    pub store_member: Option<Account<'info, StoreMember>>,
+    // The purchase order this shipment fulfils, required when it has one
+    #[account(
+        mut,
+        constraint = shipment.purchase_order() == Some(purchase_order.key())
+    )]
+    pub purchase_order: Option<Account<'info, PurchaseOrder>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(tracking_id: String)]
+pub struct InitiateReturn<'info> {
+    // Longer values cannot be used as PDA seeds
+    #[account(
+        mut,
+        constraint = tracking_id.len() <= MAX_TRACKING_ID_LEN @ SupplyChainError::StringTooLong
+    )]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_store_role(&store, &store_member, authority.key(), STORE_ROLE_RECEIVING)
+    )]
+    pub store: Account<'info, Store>,
+    pub store_member: Option<Account<'info, StoreMember>>,
+    #[account(
+        mut,
+        constraint = original_shipment.destination == store.key()
+    )]
+    pub original_shipment: Account<'info, ShipmentRecord>,
+    // Global tracking ID registry entry; an already claimed ID fails here
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = TrackingIdRecord::space(),
+        seeds = [TRACKING_ID_SEED, tracking_id.as_bytes()],
+        bump,
+        constraint = !tracking_id_record.is_claimed() @ SupplyChainError::DuplicateEntry
+    )]
+    pub tracking_id_record: Account<'info, TrackingIdRecord>,
+    #[account(
+        init,
+        payer = authority,
+        space = ShipmentRecord::space(),
+        seeds = [SHIPMENT_SEED, original_shipment.supplier.as_ref(), tracking_id.as_bytes()],
+        bump
+    )]
+    pub return_shipment: Account<'info, ShipmentRecord>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReviseEta<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    // Either the supplier or the credentialed carrier assigned to the shipment can revise the ETA
+    #[account(
+        mut,
+        constraint = (shipment.supplier == supplier.key() && has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_SHIPPING)) ||
+                    (authority_credentials.is_some() &&
+                     authority_credentials.as_ref().unwrap().authority == authority.key() &&
+                     authority_credentials.as_ref().unwrap().is_verifier &&
+                     carrier_assignment.as_ref().is_some_and(|assignment| assignment.assigns(shipment.key(), authority.key())))
+    )]
+    pub shipment: Account<'info, ShipmentRecord>,
+    pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    pub carrier_assignment: Option<Account<'info, ShipmentCarrier>>,
+    #[account(
+        init,
+        payer = authority,
+        space = EtaRevision::space(),
+        seeds = [
+            ETA_REVISION_SEED,
+            shipment.key().as_ref(),
+            &shipment.eta_revision_count.to_le_bytes()
+        ],
+        bump
+    )]
+    pub eta_revision: Account<'info, EtaRevision>,
+    pub authority_credentials: Option<Account<'info, VerifierCredential>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AssignShipmentCarrier<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_SHIPPING)
+    )]
+    pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    #[account(
+        constraint = shipment.supplier == supplier.key()
+    )]
+    pub shipment: Account<'info, ShipmentRecord>,
+    #[account(
+        constraint = carrier_credentials.is_verifier @ SupplyChainError::UnauthorizedVerifier
+    )]
+    pub carrier_credentials: Account<'info, VerifierCredential>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = ShipmentCarrier::space(),
+        seeds = [SHIPMENT_CARRIER_SEED, shipment.key().as_ref()],
+        bump
+    )]
+    pub carrier_assignment: Account<'info, ShipmentCarrier>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FlagOverdueShipment<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    #[account(mut)]
+    pub shipment: Account<'info, ShipmentRecord>,
+    #[account(
+        mut,
+        constraint = shipment.supplier == supplier.key()
+    )]
+    pub supplier: Account<'info, Supplier>,
+    #[account(
+        init,
+        payer = cranker,
+        space = ShipmentException::space(),
+        seeds = [
+            SHIPMENT_EXCEPTION_SEED,
+            shipment.key().as_ref(),
+            &shipment.exception_count.to_le_bytes()
+        ],
+        bump
+    )]
+    pub exception: Account<'info, ShipmentException>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
 }
 
 // Event definitions
@@ -241,6 +1023,33 @@ pub struct ShipmentStatusUpdatedEvent {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct ShipmentReturnInitiatedEvent {
+    pub original_shipment: Pubkey,
+    pub return_shipment: Pubkey,
+    pub initiated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EtaRevisedEvent {
+    pub shipment: Pubkey,
+    pub revision: u16,
+    pub previous_eta: i64,
+    pub new_eta: i64,
+    pub reason_code: u8,
+    pub revised_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ShipmentCarrierAssignedEvent {
+    pub shipment: Pubkey,
+    pub carrier: Pubkey,
+    pub assigned_by: Pubkey,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct ShipmentVerifiedEvent {
     pub shipment: Pubkey,
@@ -256,6 +1065,15 @@ pub struct ShipmentExceptionEvent {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct ShipmentExceptionResolvedEvent {
+    pub shipment: Pubkey,
+    pub exception: Pubkey,
+    pub resolved_by: Pubkey,
+    pub outcome: u8,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct AgreementCompletedEvent {
     pub agreement: Pubkey,
@@ -263,60 +1081,235 @@ pub struct AgreementCompletedEvent {
 }
 
 // Shipment tracking functionality
+pub fn set_shipment_route(
+    ctx: Context<SetShipmentRoute>,
+    checkpoints: Vec<RouteCheckpoint>,
+    corridor_meters: u32
+) -> Result<()> {
+    let shipment = &mut ctx.accounts.shipment;
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    // The route can only be planned before the shipment is picked up
+    require!(
+        shipment.status == ShipmentStatus::Created as u8,
+        SupplyChainError::InvalidStatusTransition
+    );
+    
+    require!(
+        !checkpoints.is_empty() && checkpoints.len() <= MAX_ROUTE_CHECKPOINTS,
+        SupplyChainError::InvalidData
+    );
+    
+    require!(
+        corridor_meters > 0,
+        SupplyChainError::InvalidData
+    );
+    
+    // Checkpoints must be valid coordinates with windows in visiting order
+    let mut previous_window_start = i64::MIN;
+    for checkpoint in checkpoints.iter() {
+        require!(
+            is_valid_coordinate(checkpoint.latitude, checkpoint.longitude) &&
+            checkpoint.radius_meters > 0,
+            SupplyChainError::InvalidData
+        );
+        require!(
+            checkpoint.window_start <= checkpoint.window_end &&
+            checkpoint.window_start >= previous_window_start,
+            SupplyChainError::InvalidTimestamp
+        );
+        previous_window_start = checkpoint.window_start;
+    }
+    
+    let checkpoint_count = checkpoints.len() as u8;
+    shipment.route_plan = Some(RoutePlan {
+        checkpoints,
+        corridor_meters,
+        next_checkpoint: 0,
+    });
+    
+    emit!(ShipmentRouteSetEvent {
+        shipment: shipment.key(),
+        checkpoint_count,
+        corridor_meters,
+        timestamp: current_time,
+    });
+    
+    Ok(())
+}
+
 pub fn record_shipment_location(
     ctx: Context<RecordShipmentLocation>,
     latitude: f64,
     longitude: f64,
     location_name: String
 ) -> Result<()> {
-    let location_record = &mut ctx.accounts.location_record;
+    let location_page = &mut ctx.accounts.location_page;
+    let shipment = &mut ctx.accounts.shipment;
+    let shipment_key = shipment.key();
     let current_time = Clock::get()?.unix_timestamp;
     
     // Validate the shipment is in a status that allows location updates
     require!(
-        ctx.accounts.shipment.status == ShipmentStatus::InTransit as u8 || 
-        ctx.accounts.shipment.status == ShipmentStatus::Exception as u8,
+        shipment.status == ShipmentStatus::InTransit as u8 || 
+        shipment.status == ShipmentStatus::Exception as u8,
         SupplyChainError::InvalidStatusTransition
     );
     
-    // Record location data
-    location_record.shipment = ctx.accounts.shipment.key();
-    location_record.latitude = latitude;
-    location_record.longitude = longitude;
-    location_record.location_name = location_name;
-    location_record.timestamp = current_time;
-    location_record.recorded_by = ctx.accounts.authority.key();
+    validate_string_length(&location_name, MAX_LOCATION_LEN)?;
+    
+    require!(
+        is_valid_coordinate(latitude, longitude),
+        SupplyChainError::InvalidData
+    );
+    
+    // A fresh page is initialized when the previous one filled up
+    if location_page.fixes.is_empty() {
+        location_page.shipment = shipment_key;
+        location_page.page_index = ShipmentLocationPage::page_index_for(shipment.location_count);
+        location_page.bump = ctx.bumps.location_page;
+        location_page.version = CURRENT_ACCOUNT_VERSION;
+    }
+    
+    // Append the fix to the current page and update the shipment summary
+    let fix = LocationFix {
+        latitude,
+        longitude,
+        location_name: location_name.clone(),
+        timestamp: current_time,
+        recorded_by: ctx.accounts.authority.key(),
+    };
+    location_page.fixes.push(fix.clone());
+    shipment.latest_location = Some(fix);
+    shipment.location_count = shipment.location_count.checked_add(1)
+        .ok_or(SupplyChainError::ArithmeticError)?;
     
     // Emit event for tracking
     emit!(ShipmentLocationEvent {
-        shipment: ctx.accounts.shipment.key(),
+        shipment: shipment_key,
         latitude,
         longitude,
-        location_name: location_name.clone(),
+        location_name,
         timestamp: current_time,
     });
     
+    // Check the fix against the planned route, if there is one
+    let mut route_exception = None;
+    if let Some(route_plan) = shipment.route_plan.as_mut() {
+        match route_plan.check_fix(latitude, longitude, current_time)? {
+            RouteCheck::OnRoute => {},
+            RouteCheck::CheckpointReached(checkpoint_index) => {
+                emit!(RouteCheckpointReachedEvent {
+                    shipment: shipment_key,
+                    checkpoint_index,
+                    timestamp: current_time,
+                });
+            },
+            RouteCheck::Deviated { distance_meters } => {
+                route_exception = Some((ExceptionReason::RouteDeviation, format!(
+                    "Route deviation: {}m off the planned route", distance_meters
+                )));
+            },
+            RouteCheck::MissedWindow(checkpoint_index) => {
+                route_exception = Some((ExceptionReason::Delay, format!(
+                    "Missed time window for checkpoint {}", checkpoint_index
+                )));
+            },
+        }
+    }
+    
+    // Deviations only raise an exception for shipments that are not already in one
+    if shipment.status != ShipmentStatus::InTransit as u8 {
+        route_exception = None;
+    }
+    
+    // The exception slot is only taken when an exception is actually raised
+    match (route_exception, ctx.accounts.exception.as_mut()) {
+        (Some((reason, details)), Some(exception)) => {
+            raise_shipment_exception(
+                shipment,
+                exception,
+                &mut ctx.accounts.supplier,
+                reason as u8,
+                ExceptionSeverity::Medium as u8,
+                details,
+                ctx.accounts.authority.key(),
+                ctx.bumps.exception.ok_or(SupplyChainError::MissingRequiredField)?,
+                current_time,
+            )?;
+        },
+        (Some(_), None) => return err!(SupplyChainError::MissingRequiredField),
+        (None, Some(_)) => return err!(SupplyChainError::InvalidData),
+        (None, None) => {},
+    }
+    
     Ok(())
 }
 
+#[derive(Accounts)]
+pub struct SetShipmentRoute<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_SHIPPING)
+    )]
+    pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    #[account(
+        mut,
+        constraint = shipment.supplier == supplier.key()
+    )]
+    pub shipment: Account<'info, ShipmentRecord>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
 #[derive(Accounts)]
 pub struct RecordShipmentLocation<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
     #[account(
-        constraint = (shipment.supplier == authority.key()) || 
+        mut,
+        constraint = (shipment.supplier == supplier.key() && has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_SHIPPING)) || 
                     (authority_credentials.is_some() && 
                      authority_credentials.as_ref().unwrap().authority == authority.key() &&
                      authority_credentials.as_ref().unwrap().is_verifier)
     )]
     pub shipment: Account<'info, ShipmentRecord>,
+    #[account(
+        mut,
+        constraint = shipment.supplier == supplier.key()
+    )]
+    pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = ShipmentLocationPage::space(),
+        seeds = [
+            LOCATION_PAGE_SEED,
+            shipment.key().as_ref(),
+            &ShipmentLocationPage::page_index_for(shipment.location_count).to_le_bytes()
+        ],
+        bump
+    )]
+    pub location_page: Account<'info, ShipmentLocationPage>,
+    // Next exception slot, passed only when the fix raises a route exception
     #[account(
         init,
         payer = authority,
-        space = ShipmentLocation::space()
+        space = ShipmentException::space(),
+        seeds = [
+            SHIPMENT_EXCEPTION_SEED,
+            shipment.key().as_ref(),
+            &shipment.exception_count.to_le_bytes()
+        ],
+        bump
     )]
-    pub location_record: Account<'info, ShipmentLocation>,
+    pub exception: Option<Account<'info, ShipmentException>>,
     pub authority_credentials: Option<Account<'info, VerifierCredential>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
     pub system_program: Program<'info, System>,
 }
 
@@ -329,25 +1322,175 @@ pub struct ShipmentLocationEvent {
     pub timestamp: i64,
 }
 
-// Additional account structure for location tracking
+#[event]
+pub struct ShipmentRouteSetEvent {
+    pub shipment: Pubkey,
+    pub checkpoint_count: u8,
+    pub corridor_meters: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RouteCheckpointReachedEvent {
+    pub shipment: Pubkey,
+    pub checkpoint_index: u8,
+    pub timestamp: i64,
+}
+
+// Additional account structure mapping a tracking ID to its shipment,
+// addressed by ["tracking_id", tracking_id] so IDs are unique across all suppliers
+pub const TRACKING_ID_SEED: &[u8] = b"tracking_id";
+
 #[account]
-pub struct ShipmentLocation {
+#[derive(InitSpace)]
+pub struct TrackingIdRecord {
+    #[max_len(MAX_TRACKING_ID_LEN)]
+    pub tracking_id: String,
     pub shipment: Pubkey,
-    pub latitude: f64,
-    pub longitude: f64,
-    pub location_name: String,
+    pub supplier: Pubkey,
+    pub registered_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl TrackingIdRecord {
+    pub fn space() -> usize {
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+    
+    // Helper method to check whether the tracking ID already belongs to a shipment
+    pub fn is_claimed(&self) -> bool {
+        self.shipment != Pubkey::default()
+    }
+    
+    // Helper method to assign the tracking ID to a newly created shipment
+    pub fn claim(
+        &mut self,
+        tracking_id: &str,
+        shipment: Pubkey,
+        supplier: Pubkey,
+        bump: u8,
+        registered_at: i64,
+    ) {
+        self.tracking_id = tracking_id.to_string();
+        self.shipment = shipment;
+        self.supplier = supplier;
+        self.registered_at = registered_at;
+        self.bump = bump;
+        self.version = CURRENT_ACCOUNT_VERSION;
+    }
+}
+
+// Additional account structure recording a typed shipment exception and its remedy,
+// addressed by ["shipment_exception", shipment, index]
+pub const SHIPMENT_EXCEPTION_SEED: &[u8] = b"shipment_exception";
+
+#[account]
+#[derive(InitSpace)]
+pub struct ShipmentException {
+    pub shipment: Pubkey,
+    pub index: u16,
+    pub reason: u8,
+    pub severity: u8,
+    #[max_len(MAX_NOTES_LEN)]
+    pub details: String,
+    pub raised_by: Pubkey,
+    pub raised_at: i64,
+    pub resolved: bool,
+    pub resolved_by: Option<Pubkey>,
+    #[max_len(MAX_NOTES_LEN)]
+    pub remedy: Option<String>,
+    pub resolved_at: Option<i64>,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl ShipmentException {
+    pub fn space() -> usize {
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+}
+
+// Additional account structure recording each ETA revision,
+// addressed by ["eta_revision", shipment, revision]
+pub const ETA_REVISION_SEED: &[u8] = b"eta_revision";
+
+#[account]
+#[derive(InitSpace)]
+pub struct EtaRevision {
+    pub shipment: Pubkey,
+    pub revision: u16,
+    pub previous_eta: i64,
+    pub new_eta: i64,
+    pub reason_code: u8,
+    pub revised_by: Pubkey,
     pub timestamp: i64,
-    pub recorded_by: Pubkey,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl EtaRevision {
+    pub fn space() -> usize {
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+}
+
+// Additional account structure for location tracking.
+// Fixes are appended to fixed-capacity pages addressed by
+// ["location_page", shipment, page_index]; pages 0..=location_count / capacity
+// can be derived from the shipment alone to walk its full history.
+pub const LOCATION_PAGE_SEED: &[u8] = b"location_page";
+pub const LOCATION_PAGE_CAPACITY: u64 = 16;
+
+#[account]
+#[derive(InitSpace)]
+pub struct ShipmentLocationPage {
+    pub shipment: Pubkey,
+    pub page_index: u32,
+    #[max_len(LOCATION_PAGE_CAPACITY)]
+    pub fixes: Vec<LocationFix>,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
 }
 
-impl ShipmentLocation {
+impl ShipmentLocationPage {
     pub fn space() -> usize {
-        8 +    // discriminator
-        32 +   // shipment: Pubkey
-        8 +    // latitude: f64
-        8 +    // longitude: f64
-        64 +   // location_name: String (max assumed)
-        8 +    // timestamp: i64
-        32     // recorded_by: Pubkey
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+    
+    // Page that the next fix for a shipment with `location_count` fixes lands in
+    pub fn page_index_for(location_count: u64) -> u32 {
+        (location_count / LOCATION_PAGE_CAPACITY) as u32
+    }
+}
+
+// Additional account structure recording the carrier assigned to a shipment,
+// addressed by ["shipment_carrier", shipment]
+pub const SHIPMENT_CARRIER_SEED: &[u8] = b"shipment_carrier";
+
+#[account]
+#[derive(InitSpace)]
+pub struct ShipmentCarrier {
+    pub shipment: Pubkey,
+    pub carrier: Pubkey,
+    pub assigned_by: Pubkey,
+    pub assigned_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl ShipmentCarrier {
+    pub fn space() -> usize {
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+    
+    // Helper method to check if this assignment names `carrier` for `shipment`
+    pub fn assigns(&self, shipment: Pubkey, carrier: Pubkey) -> bool {
+        self.shipment == shipment && self.carrier == carrier
     }
 }
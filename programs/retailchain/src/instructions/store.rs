@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use super::validate_string_length;
+use super::config::{ProgramConfig, CONFIG_SEED};
+
+// Store registration
+pub fn initialize_store(
+    ctx: Context<InitializeStore>,
+    name: String,
+    location: String
+) -> Result<()> {
+    validate_string_length(&name, MAX_NAME_LEN)?;
+    validate_string_length(&location, MAX_LOCATION_LEN)?;
+
+    let store = &mut ctx.accounts.store;
+    store.owner = ctx.accounts.owner.key();
+    store.name = name;
+    store.location = location;
+    store.total_products = 0;
+    store.is_active = true;
+    store.version = CURRENT_ACCOUNT_VERSION;
+    store.approval_threshold = 0;
+    store.approvals_required = 0;
+
+    emit!(StoreInitializedEvent {
+        store: store.key(),
+        owner: store.owner,
+        name: store.name.clone(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Account contexts for store operations
+#[derive(Accounts)]
+pub struct InitializeStore<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = Store::space()
+    )]
+    pub store: Account<'info, Store>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+// Events
+#[event]
+pub struct StoreInitializedEvent {
+    pub store: Pubkey,
+    pub owner: Pubkey,
+    pub name: String,
+    pub timestamp: i64,
+}
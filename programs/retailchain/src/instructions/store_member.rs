@@ -0,0 +1,220 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::SupplyChainError;
+use super::has_store_role;
+use super::config::{ProgramConfig, CONFIG_SEED};
+
+// Store staff management.
+// The owner (or a member holding the admin role) grants staff members role bits;
+// store-side checks accept the owner or a member holding the role they require.
+pub fn add_store_member(
+    ctx: Context<AddStoreMember>,
+    member: Pubkey,
+    roles: u8
+) -> Result<()> {
+    validate_roles_grant(&ctx.accounts.store, ctx.accounts.authority.key(), roles)?;
+
+    let store_member = &mut ctx.accounts.store_member;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    store_member.store = ctx.accounts.store.key();
+    store_member.member = member;
+    store_member.roles = roles;
+    store_member.added_by = ctx.accounts.authority.key();
+    store_member.added_at = current_time;
+    store_member.bump = ctx.bumps.store_member;
+    store_member.version = CURRENT_ACCOUNT_VERSION;
+
+    emit!(StoreMemberUpdatedEvent {
+        store: store_member.store,
+        member,
+        old_roles: 0,
+        new_roles: roles,
+        updated_by: ctx.accounts.authority.key(),
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+pub fn update_store_member(
+    ctx: Context<UpdateStoreMember>,
+    roles: u8
+) -> Result<()> {
+    let store = &ctx.accounts.store;
+    let authority = ctx.accounts.authority.key();
+
+    validate_roles_grant(store, authority, roles)?;
+
+    // Only the owner can change the roles of another admin or an approver
+    let store_member = &mut ctx.accounts.store_member;
+    require!(
+        store_member.roles & STORE_ROLE_OWNER_GRANTED == 0 || store.owner == authority,
+        SupplyChainError::Unauthorized
+    );
+
+    let old_roles = store_member.roles;
+    store_member.roles = roles;
+
+    emit!(StoreMemberUpdatedEvent {
+        store: store_member.store,
+        member: store_member.member,
+        old_roles,
+        new_roles: roles,
+        updated_by: authority,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn remove_store_member(
+    ctx: Context<RemoveStoreMember>
+) -> Result<()> {
+    let store = &ctx.accounts.store;
+    let authority = ctx.accounts.authority.key();
+    let store_member = &ctx.accounts.store_member;
+
+    require!(
+        store_member.roles & STORE_ROLE_OWNER_GRANTED == 0 || store.owner == authority,
+        SupplyChainError::Unauthorized
+    );
+
+    emit!(StoreMemberUpdatedEvent {
+        store: store_member.store,
+        member: store_member.member,
+        old_roles: store_member.roles,
+        new_roles: 0,
+        updated_by: authority,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Roles must be known bits, and only the owner can hand out the admin and approver roles
+fn validate_roles_grant(store: &Store, authority: Pubkey, roles: u8) -> Result<()> {
+    require!(
+        roles != 0 && roles & !STORE_ROLE_ALL == 0,
+        SupplyChainError::InvalidData
+    );
+    require!(
+        roles & STORE_ROLE_OWNER_GRANTED == 0 || store.owner == authority,
+        SupplyChainError::Unauthorized
+    );
+    Ok(())
+}
+
+// Account contexts for store member operations
+// `authority_membership` is the signer's own membership when they are an admin member
+#[derive(Accounts)]
+#[instruction(member: Pubkey)]
+pub struct AddStoreMember<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_store_role(&store, &authority_membership, authority.key(), STORE_ROLE_ADMIN)
+    )]
+    pub store: Account<'info, Store>,
+    pub authority_membership: Option<Account<'info, StoreMember>>,
+    #[account(
+        init,
+        payer = authority,
+        space = StoreMember::space(),
+        seeds = [STORE_MEMBER_SEED, store.key().as_ref(), member.as_ref()],
+        bump
+    )]
+    pub store_member: Account<'info, StoreMember>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateStoreMember<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_store_role(&store, &authority_membership, authority.key(), STORE_ROLE_ADMIN)
+    )]
+    pub store: Account<'info, Store>,
+    pub authority_membership: Option<Account<'info, StoreMember>>,
+    #[account(
+        mut,
+        seeds = [STORE_MEMBER_SEED, store.key().as_ref(), store_member.member.as_ref()],
+        bump = store_member.bump
+    )]
+    pub store_member: Account<'info, StoreMember>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveStoreMember<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_store_role(&store, &authority_membership, authority.key(), STORE_ROLE_ADMIN)
+    )]
+    pub store: Account<'info, Store>,
+    pub authority_membership: Option<Account<'info, StoreMember>>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [STORE_MEMBER_SEED, store.key().as_ref(), store_member.member.as_ref()],
+        bump = store_member.bump
+    )]
+    pub store_member: Account<'info, StoreMember>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// Events
+#[event]
+pub struct StoreMemberUpdatedEvent {
+    pub store: Pubkey,
+    pub member: Pubkey,
+    pub old_roles: u8,
+    pub new_roles: u8,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+// Accounts
+// Store members are addressed by ["store_member", store, member]
+pub const STORE_MEMBER_SEED: &[u8] = b"store_member";
+
+// Role bits held by store members
+pub const STORE_ROLE_INVENTORY: u8 = 1 << 0;
+pub const STORE_ROLE_RECEIVING: u8 = 1 << 1;
+pub const STORE_ROLE_PURCHASING: u8 = 1 << 2;
+pub const STORE_ROLE_ADMIN: u8 = 1 << 3;
+pub const STORE_ROLE_APPROVER: u8 = 1 << 4;
+pub const STORE_ROLE_ALL: u8 = STORE_ROLE_INVENTORY | STORE_ROLE_RECEIVING |
+    STORE_ROLE_PURCHASING | STORE_ROLE_ADMIN | STORE_ROLE_APPROVER;
+// Roles only the owner can grant, change or revoke; an admin handing out approver
+// roles could satisfy an approval policy alone
+pub const STORE_ROLE_OWNER_GRANTED: u8 = STORE_ROLE_ADMIN | STORE_ROLE_APPROVER;
+
+#[account]
+#[derive(InitSpace)]
+pub struct StoreMember {
+    pub store: Pubkey,
+    pub member: Pubkey,
+    pub roles: u8,
+    pub added_by: Pubkey,
+    pub added_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl StoreMember {
+    pub fn space() -> usize {
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+
+    // True if this membership lets `member` act for `store` with any of `roles`
+    pub fn grants(&self, store: Pubkey, member: Pubkey, roles: u8) -> bool {
+        self.store == store && self.member == member && self.roles & roles != 0
+    }
+}
@@ -1,6 +1,12 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::errors::SupplyChainError;
+use super::{validate_string_length, validate_supplier_authority, validate_supplier_in_good_standing, has_store_role, has_supplier_role};
+use super::store_member::{StoreMember, STORE_ROLE_PURCHASING};
+use super::supplier_member::{SupplierMember, SUPPLIER_ROLE_ADMIN, SUPPLIER_ROLE_CATALOG};
+use super::config::{ProgramConfig, CONFIG_SEED};
+use super::certification::{validate_certification, Certification};
+use super::pricing::{PriceSchedule, PRICE_SCHEDULE_SEED};
 
 // Supplier registration and management
 pub fn register_supplier(
@@ -10,18 +16,28 @@ pub fn register_supplier(
     description: String
 ) -> Result<()> {
     let supplier = &mut ctx.accounts.supplier;
+    
+    validate_string_length(&name, MAX_ORGANIZATION_LEN)?;
+    validate_string_length(&certification, MAX_CERTIFICATION_LEN)?;
+    validate_string_length(&description, MAX_DESCRIPTION_LEN)?;
+    
     supplier.key = ctx.accounts.authority.key();
     supplier.name = name;
     supplier.certification = certification;
     supplier.description = description;
     supplier.products_supplied = 0;
-    supplier.is_verified = false;
+    supplier.verification_status = VerificationStatus::Unverified as u8;
     supplier.rating = 0;
     supplier.created_at = Clock::get()?.unix_timestamp;
+    supplier.bump = ctx.bumps.supplier;
+    supplier.version = CURRENT_ACCOUNT_VERSION;
+    supplier.registered_key = supplier.key;
+    supplier.key_rotation_count = 0;
     
     Ok(())
 }
 
+// Also re-approves a supplier awaiting re-verification
 pub fn verify_supplier(
     ctx: Context<VerifySupplier>
 ) -> Result<()> {
@@ -29,12 +45,28 @@ pub fn verify_supplier(
     
     // Only allow verification by authorized verifiers
     require!(
-        ctx.accounts.authority.key() == supplier.key || 
-        ctx.accounts.authority_credentials.is_verifier, 
+        ctx.accounts.authority_credentials.is_verifier,
         SupplyChainError::UnauthorizedVerifier
     );
     
-    supplier.is_verified = true;
+    // Re-approval closes the re-verification window opened by the material change
+    require!(
+        supplier.verification_status != VerificationStatus::PendingReverification as u8 ||
+        ctx.accounts.reverification.is_some(),
+        SupplyChainError::MissingRequiredField
+    );
+    
+    let old_status = supplier.verification_status;
+    supplier.verification_status = VerificationStatus::Verified as u8;
+    
+    emit!(SupplierVerificationChangedEvent {
+        supplier: supplier.key(),
+        old_status,
+        new_status: supplier.verification_status,
+        grace_ends_at: None,
+        changed_by: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
     
     Ok(())
 }
@@ -44,46 +76,143 @@ pub fn update_supplier(
     certification: Option<String>,
     description: Option<String>
 ) -> Result<()> {
-    let supplier = &mut ctx.accounts.supplier;
+    // Only the supplier themselves or their admin operators can update their information
+    validate_supplier_authority(
+        &ctx.accounts.authority,
+        &ctx.accounts.supplier,
+        &ctx.accounts.supplier_member,
+        SUPPLIER_ROLE_ADMIN
+    )?;
     
-    // Only the supplier themselves can update their information
-    require!(
-        ctx.accounts.authority.key() == supplier.key,
-        SupplyChainError::Unauthorized
-    );
+    let supplier = &mut ctx.accounts.supplier;
+    let current_time = Clock::get()?.unix_timestamp;
+    let mut material_change = false;
     
     if let Some(new_certification) = certification {
+        validate_string_length(&new_certification, MAX_CERTIFICATION_LEN)?;
+        material_change |= new_certification != supplier.certification;
         supplier.certification = new_certification;
     }
     
     if let Some(new_description) = description {
+        validate_string_length(&new_description, MAX_DESCRIPTION_LEN)?;
         supplier.description = new_description;
     }
     
+    // A verified supplier that changes its certification must be re-approved by a
+    // verifier; a change made while already pending does not extend the grace period.
+    // The re-verification account is only passed when it opens a window.
+    let opens_reverification = material_change && supplier.is_verified();
+    require!(
+        opens_reverification == ctx.accounts.reverification.is_some(),
+        SupplyChainError::InvalidData
+    );
+    
+    if let Some(reverification) = ctx.accounts.reverification.as_mut() {
+        reverification.supplier = supplier.key();
+        reverification.requested_at = current_time;
+        reverification.grace_ends_at = current_time.checked_add(REVERIFICATION_GRACE_PERIOD)
+            .ok_or(SupplyChainError::ArithmeticError)?;
+        reverification.bump = ctx.bumps.reverification.ok_or(SupplyChainError::MissingRequiredField)?;
+        reverification.version = CURRENT_ACCOUNT_VERSION;
+        
+        supplier.verification_status = VerificationStatus::PendingReverification as u8;
+        
+        emit!(SupplierVerificationChangedEvent {
+            supplier: supplier.key(),
+            old_status: VerificationStatus::Verified as u8,
+            new_status: supplier.verification_status,
+            grace_ends_at: Some(reverification.grace_ends_at),
+            changed_by: ctx.accounts.authority.key(),
+            timestamp: current_time,
+        });
+    }
+    
     Ok(())
 }
 
+// Each completed agreement yields at most one review; the supplier's rating is the
+// average over all of them. Dimension scores are optional and kept on the review.
 pub fn rate_supplier(
     ctx: Context<RateSupplier>,
-    rating: u8
+    rating: u8,
+    quality: Option<u8>,
+    timeliness: Option<u8>,
+    communication: Option<u8>
 ) -> Result<()> {
     let supplier = &mut ctx.accounts.supplier;
     
-    // Rating must be between 0 and 5
+    // Ratings must be between 0 and 5
     require!(
-        rating <= 5,
-        SupplyChainError::InvalidData
+        rating <= MAX_RATING &&
+        [quality, timeliness, communication].iter().flatten().all(|score| *score <= MAX_RATING),
+        SupplyChainError::InvalidRating
     );
     
     // Only stores that have completed transactions with this supplier can rate
     require!(
         ctx.accounts.agreement.store == ctx.accounts.store.key() &&
-        ctx.accounts.agreement.supplier == supplier.key &&
+        ctx.accounts.agreement.supplier == supplier.key() &&
         ctx.accounts.agreement.status == AgreementStatus::Completed as u8,
         SupplyChainError::Unauthorized
     );
     
-    supplier.rating = rating;
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    let review = &mut ctx.accounts.review;
+    review.supplier = supplier.key();
+    review.store = ctx.accounts.store.key();
+    review.agreement = ctx.accounts.agreement.key();
+    review.reviewer = ctx.accounts.authority.key();
+    review.rating = rating;
+    review.quality = quality;
+    review.timeliness = timeliness;
+    review.communication = communication;
+    review.created_at = current_time;
+    review.bump = ctx.bumps.review;
+    review.version = CURRENT_ACCOUNT_VERSION;
+    
+    supplier.record_rating(rating)?;
+    
+    emit!(SupplierReviewedEvent {
+        supplier: supplier.key(),
+        agreement: review.agreement,
+        rating,
+        rating_average: supplier.rating_average,
+        rating_count: supplier.rating_count,
+        timestamp: current_time,
+    });
+    
+    Ok(())
+}
+
+// Moves the supplier to a new signing key. Agreements, shipments and catalog entries
+// reference the supplier account, whose address stays derived from the registration
+// key, so they all carry over to the new key.
+pub fn rotate_supplier_key(
+    ctx: Context<RotateSupplierKey>,
+    new_key: Pubkey
+) -> Result<()> {
+    let supplier = &mut ctx.accounts.supplier;
+    
+    require!(
+        new_key != Pubkey::default() && new_key != supplier.key,
+        SupplyChainError::InvalidData
+    );
+    
+    let old_key = supplier.key;
+    supplier.registered_key = supplier.registration_key();
+    supplier.key = new_key;
+    supplier.key_rotation_count = supplier.key_rotation_count.checked_add(1)
+        .ok_or(SupplyChainError::ArithmeticError)?;
+    
+    emit!(SupplierKeyRotatedEvent {
+        supplier: supplier.key(),
+        old_key,
+        new_key,
+        rotation: supplier.key_rotation_count,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
     
     Ok(())
 }
@@ -96,9 +225,13 @@ pub struct RegisterSupplier<'info> {
     #[account(
         init,
         payer = authority,
-        space = Supplier::space()
+        space = Supplier::space(),
+        seeds = [SUPPLIER_SEED, authority.key().as_ref()],
+        bump
     )]
     pub supplier: Account<'info, Supplier>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
     pub system_program: Program<'info, System>,
 }
 
@@ -113,6 +246,19 @@ pub struct VerifySupplier<'info> {
     pub authority_credentials: Account<'info, VerifierCredential>,
     #[account(mut)]
     pub supplier: Account<'info, Supplier>,
+    /// CHECK: receives the re-verification rent; address is checked against the supplier's key
+    #[account(mut, address = supplier.key)]
+    pub supplier_wallet: UncheckedAccount<'info>,
+    // Required when the supplier is awaiting re-verification
+    #[account(
+        mut,
+        close = supplier_wallet,
+        seeds = [SUPPLIER_REVERIFICATION_SEED, supplier.key().as_ref()],
+        bump = reverification.bump
+    )]
+    pub reverification: Option<Account<'info, SupplierReverification>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
 }
 
 #[derive(Accounts)]
@@ -121,9 +267,37 @@ pub struct UpdateSupplier<'info> {
     pub authority: Signer<'info>,
     #[account(
         mut,
-        constraint = supplier.key == authority.key()
+        constraint = has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_ADMIN)
     )]
     pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    // Required only when a material change opens a re-verification window
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = SupplierReverification::space(),
+        seeds = [SUPPLIER_REVERIFICATION_SEED, supplier.key().as_ref()],
+        bump
+    )]
+    pub reverification: Option<Account<'info, SupplierReverification>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+// Only the current key can rotate itself; operators cannot take over the supplier
+#[derive(Accounts)]
+pub struct RotateSupplierKey<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [SUPPLIER_SEED, supplier.registration_key().as_ref()],
+        bump = supplier.bump,
+        constraint = supplier.key == authority.key() @ SupplyChainError::Unauthorized
+    )]
+    pub supplier: Account<'info, Supplier>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
 }
 
 #[derive(Accounts)]
@@ -131,17 +305,29 @@ pub struct RateSupplier<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
     #[account(
-        constraint = store.owner == authority.key()
+        constraint = has_store_role(&store, &store_member, authority.key(), STORE_ROLE_PURCHASING)
     )]
     pub store: Account<'info, Store>,
+    pub store_member: Option<Account<'info, StoreMember>>,
     #[account(
         constraint = agreement.store == store.key(),
-        constraint = agreement.supplier == supplier.key,
+        constraint = agreement.supplier == supplier.key(),
         constraint = agreement.status == AgreementStatus::Completed as u8
     )]
     pub agreement: Account<'info, SupplyAgreement>,
     #[account(mut)]
     pub supplier: Account<'info, Supplier>,
+    #[account(
+        init,
+        payer = authority,
+        space = SupplierReview::space(),
+        seeds = [SUPPLIER_REVIEW_SEED, agreement.key().as_ref()],
+        bump
+    )]
+    pub review: Account<'info, SupplierReview>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
 }
 
 // Additional supplier-product relationship functionality
@@ -150,24 +336,36 @@ pub fn add_product_to_supplier_catalog(
     name: String,
     description: String,
     price: u64,
-    available_quantity: u64
+    available_quantity: u64,
+    required_certification: Option<u8>
 ) -> Result<()> {
     let supplier_product = &mut ctx.accounts.supplier_product;
     let supplier = &mut ctx.accounts.supplier;
+    let current_time = Clock::get()?.unix_timestamp;
     
-    // Verify the supplier is verified before they can add products
-    require!(
-        supplier.is_verified,
-        SupplyChainError::VerificationRequired
-    );
+    // Verify the supplier is verified (or within its re-verification grace period)
+    // before they can add products
+    validate_supplier_in_good_standing(supplier, &ctx.accounts.reverification, current_time)?;
+    
+    validate_string_length(&name, MAX_NAME_LEN)?;
+    validate_string_length(&description, MAX_DESCRIPTION_LEN)?;
+    
+    // Products that need a certification can only be listed while it is valid
+    if let Some(cert_type) = required_certification {
+        validate_certification(&ctx.accounts.certification, supplier.key(), cert_type, current_time)?;
+    }
     
     // Set up the supplier product
-    supplier_product.supplier = supplier.key;
+    supplier_product.supplier = supplier.key();
     supplier_product.name = name;
     supplier_product.description = description;
     supplier_product.price = price;
     supplier_product.available_quantity = available_quantity;
-    supplier_product.created_at = Clock::get()?.unix_timestamp;
+    supplier_product.created_at = current_time;
+    supplier_product.bump = ctx.bumps.supplier_product;
+    supplier_product.version = CURRENT_ACCOUNT_VERSION;
+    supplier_product.set_required_certification(required_certification);
+    supplier_product.is_discontinued = false;
     
     // Increment the supplier's product count
     supplier.products_supplied = supplier.products_supplied.checked_add(1)
@@ -177,43 +375,326 @@ pub fn add_product_to_supplier_catalog(
 }
 
 #[derive(Accounts)]
+#[instruction(name: String)]
 pub struct AddProductToSupplierCatalog<'info> {
-    #[account(mut)]
+    // Longer values cannot be used as PDA seeds
+    #[account(
+        mut,
+        constraint = name.len() <= MAX_NAME_LEN @ SupplyChainError::StringTooLong
+    )]
     pub authority: Signer<'info>,
     #[account(
         mut,
-        constraint = supplier.key == authority.key(),
-        constraint = supplier.is_verified
+        constraint = has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_CATALOG)
     )]
     pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    pub reverification: Option<Account<'info, SupplierReverification>>,
     #[account(
         init,
         payer = authority,
-        space = SupplierProduct::space()
+        space = SupplierProduct::space(),
+        seeds = [SUPPLIER_PRODUCT_SEED, supplier.key().as_ref(), name.as_bytes()],
+        bump
     )]
     pub supplier_product: Account<'info, SupplierProduct>,
+    pub certification: Option<Account<'info, Certification>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
     pub system_program: Program<'info, System>,
 }
 
-// Additional account structure for supplier catalog
+// Catalog maintenance
+pub fn update_supplier_product(
+    ctx: Context<UpdateSupplierProduct>,
+    price: Option<u64>,
+    available_quantity: Option<u64>
+) -> Result<()> {
+    let supplier_product = &mut ctx.accounts.supplier_product;
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    require!(
+        !supplier_product.is_discontinued,
+        SupplyChainError::ProductDiscontinued
+    );
+    
+    if let Some(new_price) = price {
+        require!(
+            new_price > 0,
+            SupplyChainError::InvalidPrice
+        );
+        
+        let old_price = supplier_product.price;
+        if new_price != old_price {
+            supplier_product.price = new_price;
+            
+            emit!(SupplierProductPriceChangedEvent {
+                supplier_product: supplier_product.key(),
+                supplier: supplier_product.supplier,
+                old_price,
+                new_price,
+                updated_by: ctx.accounts.authority.key(),
+                timestamp: current_time,
+            });
+        }
+    }
+    
+    if let Some(new_quantity) = available_quantity {
+        supplier_product.available_quantity = new_quantity;
+    }
+    
+    Ok(())
+}
+
+// Discontinued products stay in the catalog for reference but can no longer change
+pub fn discontinue_supplier_product(
+    ctx: Context<UpdateSupplierProduct>
+) -> Result<()> {
+    let supplier_product = &mut ctx.accounts.supplier_product;
+    
+    require!(
+        !supplier_product.is_discontinued,
+        SupplyChainError::AlreadyInStatus
+    );
+    
+    supplier_product.is_discontinued = true;
+    supplier_product.available_quantity = 0;
+    
+    emit!(SupplierProductDiscontinuedEvent {
+        supplier_product: supplier_product.key(),
+        supplier: supplier_product.supplier,
+        discontinued_by: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+pub fn remove_supplier_product(
+    ctx: Context<RemoveSupplierProduct>
+) -> Result<()> {
+    let supplier = &mut ctx.accounts.supplier;
+    let supplier_product = &ctx.accounts.supplier_product;
+    
+    // Stores must be able to see a product is going away before it disappears
+    require!(
+        supplier_product.is_discontinued,
+        SupplyChainError::ProductNotDiscontinued
+    );
+    
+    // Accepted orders still draw on the entry until they are invoiced
+    require!(
+        supplier_product.reserved_quantity == 0,
+        SupplyChainError::StockReserved
+    );
+    
+    // The price schedule is closed along with the entry
+    require!(
+        !supplier_product.has_price_schedule || ctx.accounts.price_schedule.is_some(),
+        SupplyChainError::MissingRequiredField
+    );
+    
+    supplier.products_supplied = supplier.products_supplied.checked_sub(1)
+        .ok_or(SupplyChainError::ArithmeticError)?;
+    
+    emit!(SupplierProductRemovedEvent {
+        supplier_product: ctx.accounts.supplier_product.key(),
+        supplier: supplier.key(),
+        removed_by: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateSupplierProduct<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_CATALOG)
+    )]
+    pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    #[account(
+        mut,
+        seeds = [SUPPLIER_PRODUCT_SEED, supplier.key().as_ref(), supplier_product.name.as_bytes()],
+        bump = supplier_product.bump
+    )]
+    pub supplier_product: Account<'info, SupplierProduct>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveSupplierProduct<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = has_supplier_role(&supplier, &supplier_member, authority.key(), SUPPLIER_ROLE_CATALOG)
+    )]
+    pub supplier: Account<'info, Supplier>,
+    pub supplier_member: Option<Account<'info, SupplierMember>>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [SUPPLIER_PRODUCT_SEED, supplier.key().as_ref(), supplier_product.name.as_bytes()],
+        bump = supplier_product.bump
+    )]
+    pub supplier_product: Account<'info, SupplierProduct>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [PRICE_SCHEDULE_SEED, supplier_product.key().as_ref()],
+        bump = price_schedule.bump
+    )]
+    pub price_schedule: Option<Account<'info, PriceSchedule>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// Event definitions
+#[event]
+pub struct SupplierKeyRotatedEvent {
+    pub supplier: Pubkey,
+    pub old_key: Pubkey,
+    pub new_key: Pubkey,
+    pub rotation: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SupplierVerificationChangedEvent {
+    pub supplier: Pubkey,
+    pub old_status: u8,
+    pub new_status: u8,
+    pub grace_ends_at: Option<i64>,
+    pub changed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SupplierProductPriceChangedEvent {
+    pub supplier_product: Pubkey,
+    pub supplier: Pubkey,
+    pub old_price: u64,
+    pub new_price: u64,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SupplierProductDiscontinuedEvent {
+    pub supplier_product: Pubkey,
+    pub supplier: Pubkey,
+    pub discontinued_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SupplierProductRemovedEvent {
+    pub supplier_product: Pubkey,
+    pub supplier: Pubkey,
+    pub removed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SupplierReviewedEvent {
+    pub supplier: Pubkey,
+    pub agreement: Pubkey,
+    pub rating: u8,
+    pub rating_average: u16,
+    pub rating_count: u32,
+    pub timestamp: i64,
+}
+
+// Additional account structure for supplier catalog,
+// addressed by ["supplier_product", supplier, name]
+pub const SUPPLIER_PRODUCT_SEED: &[u8] = b"supplier_product";
+
 #[account]
+#[derive(InitSpace)]
 pub struct SupplierProduct {
     pub supplier: Pubkey,
+    #[max_len(MAX_NAME_LEN)]
     pub name: String,
+    #[max_len(MAX_DESCRIPTION_LEN)]
     pub description: String,
     pub price: u64,
     pub available_quantity: u64,
     pub created_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    // Certification type the supplier must hold to list this product, if
+    // `requires_certification` is set
+    pub requires_certification: bool,
+    pub certification_type: u8,
+    pub is_discontinued: bool,
+    // Units held for accepted purchase orders that have not been invoiced yet
+    pub reserved_quantity: u64,
+    pub has_price_schedule: bool,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES - 12],
 }
 
 impl SupplierProduct {
     pub fn space() -> usize {
-        8 +   // discriminator
-        32 +  // supplier: Pubkey
-        32 +  // name: String (max assumed)
-        128 + // description: String (max assumed)
-        8 +   // price: u64
-        8 +   // available_quantity: u64
-        8     // created_at: i64
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+
+    pub fn required_certification(&self) -> Option<u8> {
+        self.requires_certification.then_some(self.certification_type)
+    }
+
+    pub fn set_required_certification(&mut self, cert_type: Option<u8>) {
+        self.requires_certification = cert_type.is_some();
+        self.certification_type = cert_type.unwrap_or_default();
+    }
+}
+
+// One review per completed agreement, addressed by ["supplier_review", agreement]
+pub const SUPPLIER_REVIEW_SEED: &[u8] = b"supplier_review";
+
+#[account]
+#[derive(InitSpace)]
+pub struct SupplierReview {
+    pub supplier: Pubkey,
+    pub store: Pubkey,
+    pub agreement: Pubkey,
+    pub reviewer: Pubkey,
+    pub rating: u8,
+    pub quality: Option<u8>,
+    pub timeliness: Option<u8>,
+    pub communication: Option<u8>,
+    pub created_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl SupplierReview {
+    pub fn space() -> usize {
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+}
+
+// Re-verification window opened by a material profile change,
+// addressed by ["supplier_reverification", supplier]
+pub const SUPPLIER_REVERIFICATION_SEED: &[u8] = b"supplier_reverification";
+
+#[account]
+#[derive(InitSpace)]
+pub struct SupplierReverification {
+    pub supplier: Pubkey,
+    pub requested_at: i64,
+    pub grace_ends_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl SupplierReverification {
+    pub fn space() -> usize {
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
     }
 }
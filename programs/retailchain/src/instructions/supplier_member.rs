@@ -0,0 +1,216 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::SupplyChainError;
+use super::has_supplier_role;
+use super::config::{ProgramConfig, CONFIG_SEED};
+
+// Supplier operator management.
+// The supplier's key (or a member holding the admin role) grants operators role bits;
+// supplier-side checks accept the key or a member holding the role they require.
+pub fn add_supplier_member(
+    ctx: Context<AddSupplierMember>,
+    member: Pubkey,
+    roles: u8
+) -> Result<()> {
+    validate_roles_grant(&ctx.accounts.supplier, ctx.accounts.authority.key(), roles)?;
+
+    let supplier_member = &mut ctx.accounts.supplier_member;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    supplier_member.supplier = ctx.accounts.supplier.key();
+    supplier_member.member = member;
+    supplier_member.roles = roles;
+    supplier_member.added_by = ctx.accounts.authority.key();
+    supplier_member.added_at = current_time;
+    supplier_member.bump = ctx.bumps.supplier_member;
+    supplier_member.version = CURRENT_ACCOUNT_VERSION;
+
+    emit!(SupplierMemberUpdatedEvent {
+        supplier: supplier_member.supplier,
+        member,
+        old_roles: 0,
+        new_roles: roles,
+        updated_by: ctx.accounts.authority.key(),
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+pub fn update_supplier_member(
+    ctx: Context<UpdateSupplierMember>,
+    roles: u8
+) -> Result<()> {
+    let supplier = &ctx.accounts.supplier;
+    let authority = ctx.accounts.authority.key();
+
+    validate_roles_grant(supplier, authority, roles)?;
+
+    // Only the supplier's key can change the roles of another admin
+    let supplier_member = &mut ctx.accounts.supplier_member;
+    require!(
+        supplier_member.roles & SUPPLIER_ROLE_ADMIN == 0 || supplier.key == authority,
+        SupplyChainError::Unauthorized
+    );
+
+    let old_roles = supplier_member.roles;
+    supplier_member.roles = roles;
+
+    emit!(SupplierMemberUpdatedEvent {
+        supplier: supplier_member.supplier,
+        member: supplier_member.member,
+        old_roles,
+        new_roles: roles,
+        updated_by: authority,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn remove_supplier_member(
+    ctx: Context<RemoveSupplierMember>
+) -> Result<()> {
+    let supplier = &ctx.accounts.supplier;
+    let authority = ctx.accounts.authority.key();
+    let supplier_member = &ctx.accounts.supplier_member;
+
+    require!(
+        supplier_member.roles & SUPPLIER_ROLE_ADMIN == 0 || supplier.key == authority,
+        SupplyChainError::Unauthorized
+    );
+
+    emit!(SupplierMemberUpdatedEvent {
+        supplier: supplier_member.supplier,
+        member: supplier_member.member,
+        old_roles: supplier_member.roles,
+        new_roles: 0,
+        updated_by: authority,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Roles must be known bits, and only the supplier's key can hand out the admin role
+fn validate_roles_grant(supplier: &Supplier, authority: Pubkey, roles: u8) -> Result<()> {
+    require!(
+        roles != 0 && roles & !SUPPLIER_ROLE_ALL == 0,
+        SupplyChainError::InvalidData
+    );
+    require!(
+        roles & SUPPLIER_ROLE_ADMIN == 0 || supplier.key == authority,
+        SupplyChainError::Unauthorized
+    );
+    Ok(())
+}
+
+// Account contexts for supplier member operations
+// `authority_membership` is the signer's own membership when they are an admin member
+#[derive(Accounts)]
+#[instruction(member: Pubkey)]
+pub struct AddSupplierMember<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_supplier_role(&supplier, &authority_membership, authority.key(), SUPPLIER_ROLE_ADMIN)
+    )]
+    pub supplier: Account<'info, Supplier>,
+    pub authority_membership: Option<Account<'info, SupplierMember>>,
+    #[account(
+        init,
+        payer = authority,
+        space = SupplierMember::space(),
+        seeds = [SUPPLIER_MEMBER_SEED, supplier.key().as_ref(), member.as_ref()],
+        bump
+    )]
+    pub supplier_member: Account<'info, SupplierMember>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSupplierMember<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_supplier_role(&supplier, &authority_membership, authority.key(), SUPPLIER_ROLE_ADMIN)
+    )]
+    pub supplier: Account<'info, Supplier>,
+    pub authority_membership: Option<Account<'info, SupplierMember>>,
+    #[account(
+        mut,
+        seeds = [SUPPLIER_MEMBER_SEED, supplier.key().as_ref(), supplier_member.member.as_ref()],
+        bump = supplier_member.bump
+    )]
+    pub supplier_member: Account<'info, SupplierMember>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveSupplierMember<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = has_supplier_role(&supplier, &authority_membership, authority.key(), SUPPLIER_ROLE_ADMIN)
+    )]
+    pub supplier: Account<'info, Supplier>,
+    pub authority_membership: Option<Account<'info, SupplierMember>>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [SUPPLIER_MEMBER_SEED, supplier.key().as_ref(), supplier_member.member.as_ref()],
+        bump = supplier_member.bump
+    )]
+    pub supplier_member: Account<'info, SupplierMember>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// Events
+#[event]
+pub struct SupplierMemberUpdatedEvent {
+    pub supplier: Pubkey,
+    pub member: Pubkey,
+    pub old_roles: u8,
+    pub new_roles: u8,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+// Accounts
+// Supplier members are addressed by ["supplier_member", supplier, member]
+pub const SUPPLIER_MEMBER_SEED: &[u8] = b"supplier_member";
+
+// Role bits held by supplier members
+pub const SUPPLIER_ROLE_SHIPPING: u8 = 1 << 0;
+pub const SUPPLIER_ROLE_CATALOG: u8 = 1 << 1;
+pub const SUPPLIER_ROLE_CONTRACTS: u8 = 1 << 2;
+pub const SUPPLIER_ROLE_ADMIN: u8 = 1 << 3;
+pub const SUPPLIER_ROLE_ALL: u8 = SUPPLIER_ROLE_SHIPPING | SUPPLIER_ROLE_CATALOG |
+    SUPPLIER_ROLE_CONTRACTS | SUPPLIER_ROLE_ADMIN;
+
+#[account]
+#[derive(InitSpace)]
+pub struct SupplierMember {
+    pub supplier: Pubkey,
+    pub member: Pubkey,
+    pub roles: u8,
+    pub added_by: Pubkey,
+    pub added_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl SupplierMember {
+    pub fn space() -> usize {
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+
+    // True if this membership lets `member` act for `supplier` with any of `roles`
+    pub fn grants(&self, supplier: Pubkey, member: Pubkey, roles: u8) -> bool {
+        self.supplier == supplier && self.member == member && self.roles & roles != 0
+    }
+}
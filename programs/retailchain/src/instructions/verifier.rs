@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::SupplyChainError;
+use super::validate_string_length;
+use super::config::{ProgramConfig, CONFIG_SEED};
+
+// Verifier registration.
+// Verifier credentials gate supplier verification, certifications and shipment
+// checks, so only the program admin can issue them.
+pub fn register_verifier(
+    ctx: Context<RegisterVerifier>,
+    verification_level: u8,
+    organization: String
+) -> Result<()> {
+    validate_string_length(&organization, MAX_ORGANIZATION_LEN)?;
+
+    let credential = &mut ctx.accounts.credential;
+    credential.authority = ctx.accounts.verifier.key();
+    credential.is_verifier = true;
+    credential.verification_level = verification_level;
+    credential.organization = organization;
+    credential.version = CURRENT_ACCOUNT_VERSION;
+
+    emit!(VerifierRegisteredEvent {
+        credential: credential.key(),
+        verifier: credential.authority,
+        verification_level,
+        registered_by: ctx.accounts.admin.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Account contexts for verifier operations
+#[derive(Accounts)]
+pub struct RegisterVerifier<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    /// CHECK: the key being granted verifier credentials
+    pub verifier: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = VerifierCredential::space(),
+        seeds = [VERIFIER_SEED, verifier.key().as_ref()],
+        bump
+    )]
+    pub credential: Account<'info, VerifierCredential>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = config.admin == admin.key() @ SupplyChainError::Unauthorized
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+// Events
+#[event]
+pub struct VerifierRegisteredEvent {
+    pub credential: Pubkey,
+    pub verifier: Pubkey,
+    pub verification_level: u8,
+    pub registered_by: Pubkey,
+    pub timestamp: i64,
+}
+
+// Accounts
+// Verifier credentials are addressed by ["verifier", verifier]
+pub const VERIFIER_SEED: &[u8] = b"verifier";
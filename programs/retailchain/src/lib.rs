@@ -8,6 +8,8 @@ mod instructions;
 // Re-export for easier access
 pub use state::*;
 pub use errors::*;
+// The #[program] module re-exports the instruction names as well
+#[allow(ambiguous_glob_reexports)]
 pub use instructions::*;
 
 declare_id!("7JYPt6XXmADUzAG12ZM3763PuF7XhJmfr7oWV9g2VrcM");
@@ -17,6 +19,7 @@ pub mod retailchain {
     use super::*;
 
     // STORE MANAGEMENT
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_STORES))]
     pub fn initialize_store(
         ctx: Context<InitializeStore>,
         name: String,
@@ -25,6 +28,7 @@ pub mod retailchain {
         instructions::initialize_store(ctx, name, location)
     }
 
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_STORES))]
     pub fn add_product(
         ctx: Context<AddProduct>,
         name: String,
@@ -35,6 +39,7 @@ pub mod retailchain {
         instructions::add_product(ctx, name, description, price, quantity)
     }
 
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_STORES))]
     pub fn update_product(
         ctx: Context<UpdateProduct>,
         price: Option<u64>,
@@ -43,7 +48,49 @@ pub mod retailchain {
         instructions::update_product(ctx, price, quantity)
     }
 
+    // STORE STAFF MANAGEMENT
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_STORES))]
+    pub fn add_store_member(
+        ctx: Context<AddStoreMember>,
+        member: Pubkey,
+        roles: u8
+    ) -> Result<()> {
+        instructions::add_store_member(ctx, member, roles)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_STORES))]
+    pub fn update_store_member(
+        ctx: Context<UpdateStoreMember>,
+        roles: u8
+    ) -> Result<()> {
+        instructions::update_store_member(ctx, roles)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_STORES))]
+    pub fn remove_store_member(
+        ctx: Context<RemoveStoreMember>
+    ) -> Result<()> {
+        instructions::remove_store_member(ctx)
+    }
+
+    // PRODUCT MAPPINGS
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_STORES))]
+    pub fn set_product_mapping(
+        ctx: Context<SetProductMapping>,
+        conversion_factor: u32
+    ) -> Result<()> {
+        instructions::set_product_mapping(ctx, conversion_factor)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_STORES))]
+    pub fn remove_product_mapping(
+        ctx: Context<RemoveProductMapping>
+    ) -> Result<()> {
+        instructions::remove_product_mapping(ctx)
+    }
+
     // SUPPLIER MANAGEMENT
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SUPPLIERS))]
     pub fn register_supplier(
         ctx: Context<RegisterSupplier>,
         name: String,
@@ -53,12 +100,14 @@ pub mod retailchain {
         instructions::register_supplier(ctx, name, certification, description)
     }
 
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SUPPLIERS))]
     pub fn verify_supplier(
         ctx: Context<VerifySupplier>
     ) -> Result<()> {
         instructions::verify_supplier(ctx)
     }
 
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SUPPLIERS))]
     pub fn update_supplier(
         ctx: Context<UpdateSupplier>,
         certification: Option<String>,
@@ -67,39 +116,161 @@ pub mod retailchain {
         instructions::update_supplier(ctx, certification, description)
     }
 
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SUPPLIERS))]
     pub fn rate_supplier(
         ctx: Context<RateSupplier>,
-        rating: u8
+        rating: u8,
+        quality: Option<u8>,
+        timeliness: Option<u8>,
+        communication: Option<u8>
     ) -> Result<()> {
-        instructions::rate_supplier(ctx, rating)
+        instructions::rate_supplier(ctx, rating, quality, timeliness, communication)
     }
 
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SUPPLIERS))]
     pub fn add_product_to_supplier_catalog(
         ctx: Context<AddProductToSupplierCatalog>,
         name: String,
         description: String,
         price: u64,
-        available_quantity: u64
+        available_quantity: u64,
+        required_certification: Option<u8>
+    ) -> Result<()> {
+        instructions::add_product_to_supplier_catalog(ctx, name, description, price, available_quantity, required_certification)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SUPPLIERS))]
+    pub fn update_supplier_product(
+        ctx: Context<UpdateSupplierProduct>,
+        price: Option<u64>,
+        available_quantity: Option<u64>
+    ) -> Result<()> {
+        instructions::update_supplier_product(ctx, price, available_quantity)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SUPPLIERS))]
+    pub fn discontinue_supplier_product(
+        ctx: Context<UpdateSupplierProduct>
+    ) -> Result<()> {
+        instructions::discontinue_supplier_product(ctx)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SUPPLIERS))]
+    pub fn remove_supplier_product(
+        ctx: Context<RemoveSupplierProduct>
+    ) -> Result<()> {
+        instructions::remove_supplier_product(ctx)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SUPPLIERS))]
+    pub fn rotate_supplier_key(
+        ctx: Context<RotateSupplierKey>,
+        new_key: Pubkey
+    ) -> Result<()> {
+        instructions::rotate_supplier_key(ctx, new_key)
+    }
+
+    // CATALOG PRICING
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SUPPLIERS))]
+    pub fn set_price_schedule(
+        ctx: Context<SetPriceSchedule>,
+        price_breaks: Vec<PriceBreak>,
+        valid_from: i64,
+        valid_until: Option<i64>
+    ) -> Result<()> {
+        instructions::set_price_schedule(ctx, price_breaks, valid_from, valid_until)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SUPPLIERS))]
+    pub fn set_store_price_override(
+        ctx: Context<SetStorePriceOverride>,
+        unit_price: u64,
+        valid_from: i64,
+        valid_until: Option<i64>
+    ) -> Result<()> {
+        instructions::set_store_price_override(ctx, unit_price, valid_from, valid_until)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SUPPLIERS))]
+    pub fn remove_store_price_override(
+        ctx: Context<RemoveStorePriceOverride>
+    ) -> Result<()> {
+        instructions::remove_store_price_override(ctx)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SUPPLIERS))]
+    pub fn quote(
+        ctx: Context<Quote>,
+        quantity: u64
+    ) -> Result<u64> {
+        instructions::quote(ctx, quantity)
+    }
+
+    // SUPPLIER OPERATOR MANAGEMENT
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SUPPLIERS))]
+    pub fn add_supplier_member(
+        ctx: Context<AddSupplierMember>,
+        member: Pubkey,
+        roles: u8
+    ) -> Result<()> {
+        instructions::add_supplier_member(ctx, member, roles)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SUPPLIERS))]
+    pub fn update_supplier_member(
+        ctx: Context<UpdateSupplierMember>,
+        roles: u8
     ) -> Result<()> {
-        instructions::add_product_to_supplier_catalog(ctx, name, description, price, available_quantity)
+        instructions::update_supplier_member(ctx, roles)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SUPPLIERS))]
+    pub fn remove_supplier_member(
+        ctx: Context<RemoveSupplierMember>
+    ) -> Result<()> {
+        instructions::remove_supplier_member(ctx)
+    }
+
+    // SUPPLIER CERTIFICATIONS
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SUPPLIERS))]
+    pub fn issue_certification(
+        ctx: Context<IssueCertification>,
+        cert_type: u8,
+        expires_at: i64,
+        document_hash: [u8; 32]
+    ) -> Result<()> {
+        instructions::issue_certification(ctx, cert_type, expires_at, document_hash)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SUPPLIERS))]
+    pub fn revoke_certification(
+        ctx: Context<RevokeCertification>,
+        cert_type: u8
+    ) -> Result<()> {
+        instructions::revoke_certification(ctx, cert_type)
     }
 
     // AGREEMENT MANAGEMENT
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_AGREEMENTS))]
     pub fn create_supply_agreement(
         ctx: Context<CreateAgreement>,
         terms: String,
         deadline: i64,
-        payment_amount: u64
+        payment_amount: u64,
+        nonce: u64,
+        required_certification: Option<u8>
     ) -> Result<()> {
-        instructions::create_supply_agreement(ctx, terms, deadline, payment_amount)
+        instructions::create_supply_agreement(ctx, terms, deadline, payment_amount, nonce, required_certification)
     }
 
-    pub fn accept_agreement(
-        ctx: Context<UpdateAgreement>
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_AGREEMENTS))]
+    pub fn accept_agreement<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateAgreement<'info>>
     ) -> Result<()> {
         instructions::accept_agreement(ctx)
     }
 
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_AGREEMENTS))]
     pub fn add_products_to_agreement(
         ctx: Context<AddProductsToAgreement>,
         product_accounts: Vec<Pubkey>
@@ -107,12 +278,22 @@ pub mod retailchain {
         instructions::add_products_to_agreement(ctx, product_accounts)
     }
 
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_AGREEMENTS))]
+    pub fn fund_agreement_escrow<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FundAgreementEscrow<'info>>,
+        amount: u64
+    ) -> Result<()> {
+        instructions::fund_agreement_escrow(ctx, amount)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_AGREEMENTS))]
     pub fn complete_agreement(
         ctx: Context<CompleteAgreement>
     ) -> Result<()> {
         instructions::complete_agreement(ctx)
     }
 
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_AGREEMENTS))]
     pub fn dispute_agreement(
         ctx: Context<DisputeAgreement>,
         dispute_reason: String
@@ -120,6 +301,7 @@ pub mod retailchain {
         instructions::dispute_agreement(ctx, dispute_reason)
     }
 
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_AGREEMENTS))]
     pub fn resolve_dispute(
         ctx: Context<ResolveDispute>,
         resolution_notes: String,
@@ -128,7 +310,83 @@ pub mod retailchain {
         instructions::resolve_dispute(ctx, resolution_notes, resolution_outcome)
     }
 
+    // AGREEMENT APPROVALS
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_STORES))]
+    pub fn set_approval_policy(
+        ctx: Context<SetApprovalPolicy>,
+        approval_threshold: u64,
+        approvals_required: u8
+    ) -> Result<()> {
+        instructions::set_approval_policy(ctx, approval_threshold, approvals_required)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_AGREEMENTS))]
+    pub fn approve_agreement_action(
+        ctx: Context<ApproveAgreementAction>,
+        action: u8,
+        amount: u64
+    ) -> Result<()> {
+        instructions::approve_agreement_action(ctx, action, amount)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_AGREEMENTS))]
+    pub fn revoke_agreement_approval(
+        ctx: Context<RevokeAgreementApproval>,
+        action: u8
+    ) -> Result<()> {
+        instructions::revoke_agreement_approval(ctx, action)
+    }
+
+    // PURCHASE ORDERS
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_ORDERS))]
+    pub fn create_purchase_order<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreatePurchaseOrder<'info>>,
+        nonce: u64,
+        quantities: Vec<u64>
+    ) -> Result<()> {
+        instructions::create_purchase_order(ctx, nonce, quantities)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_ORDERS))]
+    pub fn create_restock_order<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreatePurchaseOrder<'info>>,
+        nonce: u64,
+        store_quantities: Vec<u64>
+    ) -> Result<()> {
+        instructions::create_restock_order(ctx, nonce, store_quantities)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_ORDERS))]
+    pub fn accept_purchase_order<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SupplierPurchaseOrderAction<'info>>
+    ) -> Result<()> {
+        instructions::accept_purchase_order(ctx)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_ORDERS))]
+    pub fn reject_purchase_order(
+        ctx: Context<SupplierPurchaseOrderAction>
+    ) -> Result<()> {
+        instructions::reject_purchase_order(ctx)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_ORDERS))]
+    pub fn cancel_purchase_order<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelPurchaseOrder<'info>>
+    ) -> Result<()> {
+        instructions::cancel_purchase_order(ctx)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_ORDERS))]
+    pub fn invoice_purchase_order<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SupplierPurchaseOrderAction<'info>>,
+        payment_due_at: i64
+    ) -> Result<()> {
+        instructions::invoice_purchase_order(ctx, payment_due_at)
+    }
+
     // SHIPMENT MANAGEMENT
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SHIPMENTS))]
     pub fn create_shipment(
         ctx: Context<CreateShipment>,
         tracking_id: String,
@@ -140,6 +398,7 @@ pub mod retailchain {
         instructions::create_shipment(ctx, tracking_id, origin_location, destination_location, estimated_arrival, products)
     }
 
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SHIPMENTS))]
     pub fn update_shipment_status(
         ctx: Context<UpdateShipment>,
         new_status: u8
@@ -147,19 +406,132 @@ pub mod retailchain {
         instructions::update_shipment_status(ctx, new_status)
     }
 
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SHIPMENTS | MODULE_AGREEMENTS))]
     pub fn verify_shipment_delivery(
         ctx: Context<VerifyShipmentDelivery>
     ) -> Result<()> {
         instructions::verify_shipment_delivery(ctx)
     }
 
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SHIPMENTS))]
+    pub fn designate_recipient(
+        ctx: Context<DesignateRecipient>,
+        recipient: Option<Pubkey>
+    ) -> Result<()> {
+        instructions::designate_recipient(ctx, recipient)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SHIPMENTS))]
+    pub fn submit_proof_of_delivery(
+        ctx: Context<SubmitProofOfDelivery>,
+        recipient: Pubkey,
+        received_quantities: Vec<u64>,
+        signed_at: i64,
+        photo_hash: Option<[u8; 32]>
+    ) -> Result<()> {
+        instructions::submit_proof_of_delivery(ctx, recipient, received_quantities, signed_at, photo_hash)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SHIPMENTS | MODULE_STORES))]
+    pub fn receive_shipment_stock<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReceiveShipmentStock<'info>>
+    ) -> Result<()> {
+        instructions::receive_shipment_stock(ctx)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SHIPMENTS))]
     pub fn add_shipment_exception(
-        ctx: Context<UpdateShipment>,
+        ctx: Context<AddShipmentException>,
+        reason: u8,
+        severity: u8,
         exception_details: String
     ) -> Result<()> {
-        instructions::add_shipment_exception(ctx, exception_details)
+        instructions::add_shipment_exception(ctx, reason, severity, exception_details)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SHIPMENTS))]
+    pub fn resolve_exception(
+        ctx: Context<ResolveException>,
+        remedy: String,
+        resolution_outcome: u8
+    ) -> Result<()> {
+        instructions::resolve_exception(ctx, remedy, resolution_outcome)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SHIPMENTS))]
+    pub fn cancel_shipment(
+        ctx: Context<CancelShipment>
+    ) -> Result<()> {
+        instructions::cancel_shipment(ctx)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SHIPMENTS))]
+    pub fn initiate_return(
+        ctx: Context<InitiateReturn>,
+        tracking_id: String,
+        estimated_arrival: i64
+    ) -> Result<()> {
+        instructions::initiate_return(ctx, tracking_id, estimated_arrival)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SHIPMENTS))]
+    pub fn revise_eta(
+        ctx: Context<ReviseEta>,
+        new_eta: i64,
+        reason_code: u8
+    ) -> Result<()> {
+        instructions::revise_eta(ctx, new_eta, reason_code)
     }
 
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SHIPMENTS))]
+    pub fn assign_shipment_carrier(
+        ctx: Context<AssignShipmentCarrier>
+    ) -> Result<()> {
+        instructions::assign_shipment_carrier(ctx)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SHIPMENTS))]
+    pub fn flag_overdue_shipment(
+        ctx: Context<FlagOverdueShipment>
+    ) -> Result<()> {
+        instructions::flag_overdue_shipment(ctx)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SHIPMENTS))]
+    pub fn split_shipment(
+        ctx: Context<SplitShipment>,
+        tracking_id: String,
+        products: Vec<Pubkey>
+    ) -> Result<()> {
+        instructions::split_shipment(ctx, tracking_id, products)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SHIPMENTS))]
+    pub fn consolidate_shipments<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ConsolidateShipments<'info>>,
+        tracking_id: String,
+        origin_location: String
+    ) -> Result<()> {
+        instructions::consolidate_shipments(ctx, tracking_id, origin_location)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SHIPMENTS))]
+    pub fn sync_shipment_status<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SyncShipmentStatus<'info>>
+    ) -> Result<()> {
+        instructions::sync_shipment_status(ctx)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SHIPMENTS))]
+    pub fn set_shipment_route(
+        ctx: Context<SetShipmentRoute>,
+        checkpoints: Vec<RouteCheckpoint>,
+        corridor_meters: u32
+    ) -> Result<()> {
+        instructions::set_shipment_route(ctx, checkpoints, corridor_meters)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_SHIPMENTS))]
     pub fn record_shipment_location(
         ctx: Context<RecordShipmentLocation>,
         latitude: f64,
@@ -169,7 +541,100 @@ pub mod retailchain {
         instructions::record_shipment_location(ctx, latitude, longitude, location_name)
     }
 
+    // PACKAGE MANAGEMENT
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_PACKAGES))]
+    pub fn create_package(
+        ctx: Context<CreatePackage>,
+        kind: u8,
+        label: String
+    ) -> Result<()> {
+        instructions::create_package(ctx, kind, label)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_PACKAGES))]
+    pub fn pack(
+        ctx: Context<PackPackage>
+    ) -> Result<()> {
+        instructions::pack(ctx)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_PACKAGES))]
+    pub fn unpack(
+        ctx: Context<UnpackPackage>
+    ) -> Result<()> {
+        instructions::unpack(ctx)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_PACKAGES))]
+    pub fn attach_to_shipment(
+        ctx: Context<AttachToShipment>
+    ) -> Result<()> {
+        instructions::attach_to_shipment(ctx)
+    }
+
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_PACKAGES))]
+    pub fn scan_package(
+        ctx: Context<ScanPackage>,
+        status: u8,
+        location: String
+    ) -> Result<()> {
+        instructions::scan_package(ctx, status, location)
+    }
+
+    // PROGRAM CONFIGURATION
+    // Not guarded by the pause switches so the admin can always recover
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        fee_bps: u16
+    ) -> Result<()> {
+        instructions::initialize_config(ctx, fee_bps)
+    }
+
+    pub fn propose_admin(
+        ctx: Context<UpdateConfig>,
+        new_admin: Option<Pubkey>
+    ) -> Result<()> {
+        instructions::propose_admin(ctx, new_admin)
+    }
+
+    pub fn accept_admin(
+        ctx: Context<AcceptAdmin>
+    ) -> Result<()> {
+        instructions::accept_admin(ctx)
+    }
+
+    pub fn set_pause(
+        ctx: Context<UpdateConfig>,
+        paused: bool,
+        paused_modules: u16
+    ) -> Result<()> {
+        instructions::set_pause(ctx, paused, paused_modules)
+    }
+
+    pub fn set_fee_params(
+        ctx: Context<UpdateConfig>,
+        fee_bps: u16
+    ) -> Result<()> {
+        instructions::set_fee_params(ctx, fee_bps)
+    }
+
+    pub fn withdraw_fees(
+        ctx: Context<WithdrawFees>,
+        amount: u64
+    ) -> Result<()> {
+        instructions::withdraw_fees(ctx, amount)
+    }
+
+    // ACCOUNT MIGRATION
+    // Layout upgrades stay available while paused
+    pub fn migrate_account(
+        ctx: Context<MigrateAccount>
+    ) -> Result<()> {
+        instructions::migrate_account(ctx)
+    }
+
     // IOT DATA MANAGEMENT
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_IOT))]
     pub fn add_iot_data(
         ctx: Context<AddIoTData>,
         data_type: u8,
@@ -179,6 +644,7 @@ pub mod retailchain {
         instructions::add_iot_data(ctx, data_type, value, timestamp)
     }
 
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_IOT))]
     pub fn verify_iot_data(
         ctx: Context<VerifyIoTData>
     ) -> Result<()> {
@@ -186,6 +652,7 @@ pub mod retailchain {
     }
 
     // SUPPLY CHAIN EVENT LOGGING
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_EVENTS))]
     pub fn record_supply_chain_event(
         ctx: Context<RecordEvent>,
         event_type: u8,
@@ -197,6 +664,7 @@ pub mod retailchain {
     }
 
     // VERIFIER MANAGEMENT
+    #[access_control(ctx.accounts.config.ensure_active(MODULE_VERIFIERS))]
     pub fn register_verifier(
         ctx: Context<RegisterVerifier>,
         verification_level: u8,
@@ -1,16 +1,50 @@
 use anchor_lang::prelude::*;
+use crate::errors::SupplyChainError;
 
 // ENUM DEFINITIONS
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
 pub enum ShipmentStatus {
     Created = 0,
     InTransit = 1,
     Exception = 2,
     Delivered = 3,
     Verified = 4,
+    Canceled = 5,
+    Returned = 6,
+    ReturnInTransit = 7,
+    Lost = 8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ExceptionReason {
+    Damage = 0,
+    Delay = 1,
+    CustomsHold = 2,
+    TemperatureExcursion = 3,
+    Loss = 4,
+    RouteDeviation = 5,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ExceptionSeverity {
+    Low = 0,
+    Medium = 1,
+    High = 2,
+    Critical = 3,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum EtaReasonCode {
+    Weather = 0,
+    Traffic = 1,
+    MechanicalIssue = 2,
+    CustomsHold = 3,
+    CarrierCapacity = 4,
+    SupplierDelay = 5,
+    Other = 6,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
 pub enum AgreementStatus {
     Pending = 0,
     Active = 1,
@@ -29,6 +63,47 @@ pub enum EventType {
     Payment = 5,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum PackageKind {
+    Case = 0,
+    Pallet = 1,
+    Container = 2,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum PackageStatus {
+    Staged = 0,
+    InTransit = 1,
+    AtFacility = 2,
+    Delivered = 3,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum VerificationStatus {
+    Unverified = 0,
+    Verified = 1,
+    PendingReverification = 2,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum CertificationType {
+    Iso9001 = 0,
+    Haccp = 1,
+    Organic = 2,
+    FairTrade = 3,
+    Gmp = 4,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum PurchaseOrderStatus {
+    Open = 0,
+    Accepted = 1,
+    Shipped = 2,
+    Invoiced = 3,
+    Canceled = 4,
+    Rejected = 5,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
 pub enum IoTDataType {
     Temperature = 0,
@@ -38,147 +113,386 @@ pub enum IoTDataType {
     LightExposure = 4,
 }
 
+// LENGTH LIMITS
+// Maximum byte lengths of strings and element counts of vectors stored in accounts.
+// Account sizes are derived from these, and handlers reject longer inputs with
+// `StringTooLong` / `ResourceLimitExceeded`.
+pub const MAX_NAME_LEN: usize = 32;
+pub const MAX_LABEL_LEN: usize = 32;
+pub const MAX_TRACKING_ID_LEN: usize = 32;
+pub const MAX_ORGANIZATION_LEN: usize = 64;
+pub const MAX_LOCATION_LEN: usize = 64;
+pub const MAX_DESCRIPTION_LEN: usize = 128;
+pub const MAX_CERTIFICATION_LEN: usize = 128;
+pub const MAX_IOT_VALUE_LEN: usize = 128;
+pub const MAX_TERMS_LEN: usize = 200;
+pub const MAX_NOTES_LEN: usize = 200;
+pub const MAX_METADATA_LEN: usize = 256;
+pub const MAX_AGREEMENT_PRODUCTS: usize = 16;
+pub const MAX_SHIPMENT_PRODUCTS: usize = 8;
+pub const MAX_SHIPMENT_VERIFIERS: usize = 8;
+pub const MAX_CHILD_SHIPMENTS: usize = 8;
+pub const MAX_APPROVALS: usize = 8;
+
+// SUPPLIER VERIFICATION
+// How long a supplier awaiting re-verification may keep listing products and
+// creating shipments after a material profile change
+pub const REVERIFICATION_GRACE_PERIOD: i64 = 14 * 24 * 60 * 60;
+
+// RATINGS
+pub const MAX_RATING: u8 = 5;
+// Fixed-point scale of `Supplier::rating_average`
+pub const RATING_SCALE: u16 = 100;
+
+// ACCOUNT VERSIONING
+// Every account ends with a layout version and zeroed reserved bytes. New fixed-size
+// fields are carved out of the reserved bytes; any other layout change needs an
+// upgrade step in `instructions::migration` and a version bump. Carved fields must
+// serialize to the same width whatever their value, so optional values are stored
+// as a presence flag next to the value rather than as an `Option`.
+pub const CURRENT_ACCOUNT_VERSION: u8 = 1;
+pub const ACCOUNT_RESERVED_BYTES: usize = 64;
+
+// PDA SEEDS
+pub const SUPPLIER_SEED: &[u8] = b"supplier";
+pub const AGREEMENT_SEED: &[u8] = b"agreement";
+pub const SHIPMENT_SEED: &[u8] = b"shipment";
+
 // RETAIL ENTITY ACCOUNT STRUCTURES
 #[account]
+#[derive(InitSpace)]
 pub struct Store {
     pub owner: Pubkey,
+    #[max_len(MAX_NAME_LEN)]
     pub name: String,
+    #[max_len(MAX_LOCATION_LEN)]
     pub location: String,
     pub total_products: u64,
     pub is_active: bool,
+    pub version: u8,
+    // Agreements worth at least `approval_threshold` need `approvals_required`
+    // approvers to sign off before they are accepted or funded; 0 disables the policy
+    pub approval_threshold: u64,
+    pub approvals_required: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES - 9],
 }
 
 impl Store {
     pub fn space() -> usize {
-        8 +  // discriminator
-        32 + // owner: Pubkey
-        32 + // name: String (max assumed)
-        32 + // location: String (max assumed)
-        8 +  // total_products: u64
-        1    // is_active: bool
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+
+    pub fn requires_approval(&self, amount: u64) -> bool {
+        self.approvals_required > 0 && amount >= self.approval_threshold
     }
 }
 
 #[account]
+#[derive(InitSpace)]
 pub struct Product {
     pub store: Pubkey,
+    #[max_len(MAX_NAME_LEN)]
     pub name: String,
+    #[max_len(MAX_DESCRIPTION_LEN)]
     pub description: String,
     pub price: u64,
     pub quantity: u64,
     pub created_at: i64,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
 }
 
 impl Product {
     pub fn space() -> usize {
-        8 +   // discriminator
-        32 +  // store: Pubkey
-        32 +  // name: String (max assumed)
-        128 + // description: String (max assumed)
-        8 +   // price: u64
-        8 +   // quantity: u64
-        8     // created_at: i64
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
     }
 }
 
 // SUPPLY CHAIN ACCOUNT STRUCTURES
 #[account]
+#[derive(InitSpace)]
 pub struct Supplier {
     pub key: Pubkey,
+    #[max_len(MAX_ORGANIZATION_LEN)]
     pub name: String,
+    #[max_len(MAX_CERTIFICATION_LEN)]
     pub certification: String,
+    #[max_len(MAX_DESCRIPTION_LEN)]
     pub description: String,
     pub products_supplied: u64,
-    pub is_verified: bool,
+    // Replaced the `is_verified` flag in place; 0 and 1 keep their meaning
+    pub verification_status: u8,
     pub rating: u8,
     pub created_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    // Key the account was registered (and its PDA derived) with; `key` changes on rotation
+    pub registered_key: Pubkey,
+    pub key_rotation_count: u16,
+    // Running totals over all reviews; `rating` holds the rounded average
+    pub rating_sum: u32,
+    pub rating_count: u32,
+    // Average rating scaled by RATING_SCALE (425 = 4.25)
+    pub rating_average: u16,
+    // Performance counters maintained by the shipment and agreement handlers
+    pub shipments_delivered: u32,
+    pub on_time_deliveries: u32,
+    pub exceptions_raised: u32,
+    pub agreements_completed: u32,
+    pub disputes_lost: u16,
+    // Share of deliveries made by their estimated arrival, in basis points
+    pub on_time_bps: u16,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES - 64],
 }
 
 impl Supplier {
     pub fn space() -> usize {
-        8 +   // discriminator
-        32 +  // key: Pubkey
-        64 +  // name: String (max assumed)
-        128 + // certification: String (max assumed)
-        64 +  // description: String (max assumed)
-        8 +   // products_supplied: u64
-        1 +   // is_verified: bool
-        1 +   // rating: u8
-        8     // created_at: i64
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+    
+    pub fn is_verified(&self) -> bool {
+        self.verification_status == VerificationStatus::Verified as u8
+    }
+    
+    // Accounts registered before key rotation existed never rotated, so their
+    // current key is still the registration key
+    pub fn registration_key(&self) -> Pubkey {
+        if self.registered_key == Pubkey::default() {
+            self.key
+        } else {
+            self.registered_key
+        }
+    }
+
+    // Folds one review into the running totals and recomputes the averages
+    pub fn record_rating(&mut self, rating: u8) -> Result<()> {
+        self.rating_sum = self.rating_sum.checked_add(rating as u32)
+            .ok_or(SupplyChainError::ArithmeticError)?;
+        self.rating_count = self.rating_count.checked_add(1)
+            .ok_or(SupplyChainError::ArithmeticError)?;
+
+        let sum = self.rating_sum as u64;
+        let count = self.rating_count as u64;
+        self.rating_average = (sum * RATING_SCALE as u64 / count) as u16;
+        // Rounded half up
+        self.rating = ((sum * 2 + count) / (count * 2)) as u8;
+        Ok(())
+    }
+
+    pub fn record_delivery(&mut self, on_time: bool) -> Result<()> {
+        self.shipments_delivered = self.shipments_delivered.checked_add(1)
+            .ok_or(SupplyChainError::ArithmeticError)?;
+        if on_time {
+            self.on_time_deliveries = self.on_time_deliveries.checked_add(1)
+                .ok_or(SupplyChainError::ArithmeticError)?;
+        }
+        self.on_time_bps = (self.on_time_deliveries as u64 * 10_000
+            / self.shipments_delivered as u64) as u16;
+        Ok(())
+    }
+
+    pub fn record_exception(&mut self) -> Result<()> {
+        self.exceptions_raised = self.exceptions_raised.checked_add(1)
+            .ok_or(SupplyChainError::ArithmeticError)?;
+        Ok(())
+    }
+
+    pub fn record_agreement_completed(&mut self) -> Result<()> {
+        self.agreements_completed = self.agreements_completed.checked_add(1)
+            .ok_or(SupplyChainError::ArithmeticError)?;
+        Ok(())
+    }
+
+    pub fn record_dispute_lost(&mut self) -> Result<()> {
+        self.disputes_lost = self.disputes_lost.checked_add(1)
+            .ok_or(SupplyChainError::ArithmeticError)?;
+        Ok(())
     }
 }
 
 #[account]
+#[derive(InitSpace)]
 pub struct VerifierCredential {
     pub authority: Pubkey,
     pub is_verifier: bool,
     pub verification_level: u8,
+    #[max_len(MAX_ORGANIZATION_LEN)]
     pub organization: String,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
 }
 
 impl VerifierCredential {
     pub fn space() -> usize {
-        8 +   // discriminator
-        32 +  // authority: Pubkey
-        1 +   // is_verifier: bool
-        1 +   // verification_level: u8
-        64    // organization: String (max assumed)
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
     }
 }
 
 #[account]
+#[derive(InitSpace)]
 pub struct SupplyAgreement {
     pub supplier: Pubkey,
     pub store: Pubkey,
+    #[max_len(MAX_TERMS_LEN)]
     pub terms: String,
     pub deadline: i64,
     pub payment_amount: u64,
     pub status: u8,
     pub created_at: i64,
+    #[max_len(MAX_AGREEMENT_PRODUCTS)]
     pub products: Vec<Pubkey>,
+    pub nonce: u64,
+    pub dispute_count: u16,
+    pub bump: u8,
+    pub version: u8,
+    // Lamports held in this account for the supplier until settlement
+    pub escrow_balance: u64,
+    // Certification type the supplier must hold to ship against this agreement,
+    // if `requires_certification` is set
+    pub requires_certification: bool,
+    pub certification_type: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES - 10],
 }
 
 impl SupplyAgreement {
     pub fn space() -> usize {
-        8 +    // discriminator
-        32 +   // supplier: Pubkey
-        32 +   // store: Pubkey
-        256 +  // terms: String (max assumed)
-        8 +    // deadline: i64
-        8 +    // payment_amount: u64
-        1 +    // status: u8
-        8 +    // created_at: i64
-        64     // products: Vec<Pubkey> (initial allocation for vector)
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+
+    pub fn required_certification(&self) -> Option<u8> {
+        self.requires_certification.then_some(self.certification_type)
+    }
+
+    pub fn set_required_certification(&mut self, cert_type: Option<u8>) {
+        self.requires_certification = cert_type.is_some();
+        self.certification_type = cert_type.unwrap_or_default();
     }
 }
 
 #[account]
+#[derive(InitSpace)]
 pub struct ShipmentRecord {
+    #[max_len(MAX_TRACKING_ID_LEN)]
     pub tracking_id: String,
     pub supplier: Pubkey,
     pub destination: Pubkey,
+    #[max_len(MAX_LOCATION_LEN)]
     pub origin_location: String,
+    #[max_len(MAX_LOCATION_LEN)]
     pub destination_location: String,
     pub created_at: i64,
     pub estimated_arrival: i64,
     pub status: u8,
+    #[max_len(MAX_SHIPMENT_PRODUCTS)]
     pub products: Vec<Pubkey>,
+    #[max_len(MAX_SHIPMENT_VERIFIERS)]
     pub verified_by: Vec<Pubkey>,
+    pub route_plan: Option<RoutePlan>,
+    pub location_count: u64,
+    pub latest_location: Option<LocationFix>,
+    pub eta_revision_count: u16,
+    pub linked_shipment: Option<Pubkey>,
+    pub is_return: bool,
+    pub exception_count: u16,
+    pub open_exceptions: u16,
+    pub agreement: Option<Pubkey>,
+    pub parent_shipment: Option<Pubkey>,
+    #[max_len(MAX_CHILD_SHIPMENTS)]
+    pub child_shipments: Vec<Pubkey>,
+    pub designated_recipient: Option<Pubkey>,
+    pub bump: u8,
+    pub version: u8,
+    // Purchase order this shipment fulfils, if `fulfils_purchase_order` is set
+    pub fulfils_purchase_order: bool,
+    pub purchase_order: Pubkey,
+    // ETA committed to at creation; revisions only move `estimated_arrival`
+    pub original_eta: i64,
+    // Consolidated masters only reference their children and may span several suppliers
+    pub is_master: bool,
+    // Set once the delivered goods have been added to the store's inventory
+    pub stock_received: bool,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES - 43],
 }
 
 impl ShipmentRecord {
     pub fn space() -> usize {
-        8 +    // discriminator
-        64 +   // tracking_id: String (max assumed)
-        32 +   // supplier: Pubkey
-        32 +   // destination: Pubkey
-        64 +   // origin_location: String (max assumed)
-        64 +   // destination_location: String (max assumed)
-        8 +    // created_at: i64
-        8 +    // estimated_arrival: i64
-        1 +    // status: u8
-        256 +  // products: Vec<Pubkey> (sized for 8 products)
-        256    // verified_by: Vec<Pubkey> (sized for 8 verifiers)
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
+    }
+    
+    // Helper method to initialize a new shipment record in Created status with empty tracking state
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize(
+        &mut self,
+        tracking_id: String,
+        supplier: Pubkey,
+        destination: Pubkey,
+        origin_location: String,
+        destination_location: String,
+        estimated_arrival: i64,
+        products: Vec<Pubkey>,
+        created_at: i64,
+    ) {
+        self.tracking_id = tracking_id;
+        self.supplier = supplier;
+        self.destination = destination;
+        self.origin_location = origin_location;
+        self.destination_location = destination_location;
+        self.created_at = created_at;
+        self.estimated_arrival = estimated_arrival;
+        self.original_eta = estimated_arrival;
+        self.status = ShipmentStatus::Created as u8;
+        self.products = products;
+        self.verified_by = Vec::new();
+        self.route_plan = None;
+        self.location_count = 0;
+        self.latest_location = None;
+        self.eta_revision_count = 0;
+        self.linked_shipment = None;
+        self.is_return = false;
+        self.exception_count = 0;
+        self.open_exceptions = 0;
+        self.agreement = None;
+        self.parent_shipment = None;
+        self.child_shipments = Vec::new();
+        self.designated_recipient = None;
+        self.set_purchase_order(None);
+        self.is_master = false;
+    }
+    
+    pub fn purchase_order(&self) -> Option<Pubkey> {
+        self.fulfils_purchase_order.then_some(self.purchase_order)
+    }
+    
+    pub fn set_purchase_order(&mut self, purchase_order: Option<Pubkey>) {
+        self.fulfils_purchase_order = purchase_order.is_some();
+        self.purchase_order = purchase_order.unwrap_or_default();
+    }
+    
+    // Deadline for on-time delivery. Shipments migrated before the original ETA was
+    // recorded fall back to the current one.
+    pub fn committed_eta(&self) -> i64 {
+        if self.original_eta == 0 {
+            self.estimated_arrival
+        } else {
+            self.original_eta
+        }
+    }
+    
+    // Helper method to check if the shipment was split or consolidated
+    pub fn has_children(&self) -> bool {
+        !self.child_shipments.is_empty()
+    }
+    
+    // Helper method to check if the status is rolled up from child shipments. A
+    // partially split parent keeps its own status for the products it still carries.
+    pub fn is_rolled_up(&self) -> bool {
+        self.has_children() && self.products.is_empty()
+    }
+    
+    // Supplier metrics track the supplier's own outbound legs; returns and
+    // rolled-up parents are left out
+    pub fn counts_toward_supplier_metrics(&self) -> bool {
+        !self.is_return && !self.is_rolled_up()
     }
     
     // Helper method to check if a shipment is verified by a specific authority
@@ -194,54 +508,178 @@ impl ShipmentRecord {
             2 => ShipmentStatus::Exception,
             3 => ShipmentStatus::Delivered,
             4 => ShipmentStatus::Verified,
+            5 => ShipmentStatus::Canceled,
+            6 => ShipmentStatus::Returned,
+            7 => ShipmentStatus::ReturnInTransit,
+            8 => ShipmentStatus::Lost,
             _ => ShipmentStatus::Created, // Default fallback
         }
     }
 }
 
+// A single GPS fix, stored in location history pages and as the latest location summary
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, InitSpace)]
+pub struct LocationFix {
+    pub latitude: f64,
+    pub longitude: f64,
+    #[max_len(MAX_LOCATION_LEN)]
+    pub location_name: String,
+    pub timestamp: i64,
+    pub recorded_by: Pubkey,
+}
+
+// ROUTE PLANNING STRUCTURES
+pub const MAX_ROUTE_CHECKPOINTS: usize = 8;
+const EARTH_RADIUS_METERS: f64 = 6_371_000.0;
+
+// True for a finite latitude in [-90, 90] and longitude in [-180, 180]; NaN fails
+// every range check
+pub fn is_valid_coordinate(latitude: f64, longitude: f64) -> bool {
+    (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, InitSpace)]
+pub struct RouteCheckpoint {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub radius_meters: u32,
+    pub window_start: i64,
+    pub window_end: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, InitSpace)]
+pub struct RoutePlan {
+    #[max_len(MAX_ROUTE_CHECKPOINTS)]
+    pub checkpoints: Vec<RouteCheckpoint>,
+    pub corridor_meters: u32,
+    pub next_checkpoint: u8,
+}
+
+// Outcome of checking a single location fix against a route plan
+pub enum RouteCheck {
+    OnRoute,
+    CheckpointReached(u8),
+    Deviated { distance_meters: u32 },
+    MissedWindow(u8),
+}
+
+impl RoutePlan {
+    // Checks a fix against the next unvisited checkpoint and the leg leading to it.
+    // A checkpoint counts as visited once a fix lands inside its geofence during its
+    // time window; a checkpoint whose window closes first is skipped and reported.
+    pub fn check_fix(&mut self, latitude: f64, longitude: f64, timestamp: i64) -> Result<RouteCheck> {
+        require!(
+            is_valid_coordinate(latitude, longitude),
+            SupplyChainError::InvalidData
+        );
+
+        let index = self.next_checkpoint as usize;
+        let target = match self.checkpoints.get(index) {
+            Some(target) => target.clone(),
+            None => return Ok(RouteCheck::OnRoute), // Route already completed
+        };
+
+        if timestamp > target.window_end {
+            self.next_checkpoint = self.next_checkpoint.saturating_add(1);
+            return Ok(RouteCheck::MissedWindow(index as u8));
+        }
+
+        let to_target = distance_meters(latitude, longitude, target.latitude, target.longitude);
+        if to_target <= target.radius_meters as f64 && timestamp >= target.window_start {
+            self.next_checkpoint = self.next_checkpoint.saturating_add(1);
+            return Ok(RouteCheck::CheckpointReached(index as u8));
+        }
+
+        // The approach to the first checkpoint has no leg to measure against
+        let previous = match index.checked_sub(1) {
+            Some(previous_index) => &self.checkpoints[previous_index],
+            None => return Ok(RouteCheck::OnRoute),
+        };
+
+        let off_route = distance_to_leg_meters(latitude, longitude, previous, &target);
+        let allowed = self.corridor_meters
+            .max(previous.radius_meters)
+            .max(target.radius_meters) as f64;
+        if off_route > allowed {
+            return Ok(RouteCheck::Deviated { distance_meters: off_route as u32 });
+        }
+
+        Ok(RouteCheck::OnRoute)
+    }
+}
+
+// Planar (equirectangular) offset in meters of a point from an origin; accurate
+// enough over the distances between consecutive checkpoints
+fn local_offset_meters(origin_lat: f64, origin_lon: f64, latitude: f64, longitude: f64) -> (f64, f64) {
+    let mean_lat = ((origin_lat + latitude) / 2.0).to_radians();
+    let x = (longitude - origin_lon).to_radians() * mean_lat.cos() * EARTH_RADIUS_METERS;
+    let y = (latitude - origin_lat).to_radians() * EARTH_RADIUS_METERS;
+    (x, y)
+}
+
+pub fn distance_meters(lat_a: f64, lon_a: f64, lat_b: f64, lon_b: f64) -> f64 {
+    let (x, y) = local_offset_meters(lat_a, lon_a, lat_b, lon_b);
+    (x * x + y * y).sqrt()
+}
+
+// Distance from a point to the straight leg between two checkpoints
+fn distance_to_leg_meters(
+    latitude: f64,
+    longitude: f64,
+    from: &RouteCheckpoint,
+    to: &RouteCheckpoint,
+) -> f64 {
+    let (bx, by) = local_offset_meters(from.latitude, from.longitude, to.latitude, to.longitude);
+    let (px, py) = local_offset_meters(from.latitude, from.longitude, latitude, longitude);
+    let leg_squared = bx * bx + by * by;
+    let t = if leg_squared > 0.0 {
+        ((px * bx + py * by) / leg_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (dx, dy) = (px - t * bx, py - t * by);
+    (dx * dx + dy * dy).sqrt()
+}
+
 #[account]
+#[derive(InitSpace)]
 pub struct SupplyChainEvent {
     pub event_type: u8,
     pub recorder: Pubkey,
     pub related_entity: Pubkey,
+    #[max_len(MAX_LOCATION_LEN)]
     pub location: String,
     pub timestamp: i64,
+    #[max_len(MAX_METADATA_LEN)]
     pub metadata: String,
     pub created_at: i64,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
 }
 
 impl SupplyChainEvent {
     pub fn space() -> usize {
-        8 +    // discriminator
-        1 +    // event_type: u8
-        32 +   // recorder: Pubkey
-        32 +   // related_entity: Pubkey
-        64 +   // location: String (max assumed)
-        8 +    // timestamp: i64
-        256 +  // metadata: String (max assumed)
-        8      // created_at: i64
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
     }
 }
 
 #[account]
+#[derive(InitSpace)]
 pub struct IoTDataRecord {
     pub shipment: Pubkey,
     pub data_type: u8,
+    #[max_len(MAX_IOT_VALUE_LEN)]
     pub value: String,
     pub timestamp: i64,
     pub recorder: Pubkey,
     pub is_verified: bool,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
 }
 
 impl IoTDataRecord {
     pub fn space() -> usize {
-        8 +    // discriminator
-        32 +   // shipment: Pubkey
-        1 +    // data_type: u8
-        128 +  // value: String (max assumed)
-        8 +    // timestamp: i64
-        32 +   // recorder: Pubkey
-        1      // is_verified: bool
+        8 + Self::INIT_SPACE // discriminator + derived field sizes
     }
     
     // Helper method to get the data type as enum
@@ -265,10 +703,46 @@ pub fn is_valid_status_transition(current: u8, new: u8) -> bool {
         (s1, s2) if s1 == ShipmentStatus::InTransit as u8 && s2 == ShipmentStatus::Exception as u8 => true,
         (s1, s2) if s1 == ShipmentStatus::Exception as u8 && s2 == ShipmentStatus::InTransit as u8 => true,
         (s1, s2) if s1 == ShipmentStatus::Delivered as u8 && s2 == ShipmentStatus::Verified as u8 => true,
+        (s1, s2) if s1 == ShipmentStatus::Created as u8 && s2 == ShipmentStatus::Canceled as u8 => true,
+        (s1, s2) if s1 == ShipmentStatus::Delivered as u8 && s2 == ShipmentStatus::ReturnInTransit as u8 => true,
+        (s1, s2) if s1 == ShipmentStatus::Verified as u8 && s2 == ShipmentStatus::ReturnInTransit as u8 => true,
+        (s1, s2) if s1 == ShipmentStatus::ReturnInTransit as u8 && s2 == ShipmentStatus::Returned as u8 => true,
+        (s1, s2) if s1 == ShipmentStatus::Exception as u8 && s2 == ShipmentStatus::Lost as u8 => true,
         _ => false,
     }
 }
 
+// Rolls up the status of a split or consolidated shipment from its children.
+// Canceled children are ignored; returned children count as delivered.
+pub fn rollup_shipment_status(children: &[u8]) -> u8 {
+    let active: Vec<u8> = children.iter()
+        .copied()
+        .filter(|status| *status != ShipmentStatus::Canceled as u8)
+        .collect();
+    let all = |allowed: &[u8]| active.iter().all(|status| allowed.contains(status));
+    
+    if active.is_empty() {
+        ShipmentStatus::Canceled as u8
+    } else if active.iter().any(|status| {
+        *status == ShipmentStatus::Exception as u8 || *status == ShipmentStatus::Lost as u8
+    }) {
+        ShipmentStatus::Exception as u8
+    } else if all(&[ShipmentStatus::Created as u8]) {
+        ShipmentStatus::Created as u8
+    } else if all(&[ShipmentStatus::Verified as u8]) {
+        ShipmentStatus::Verified as u8
+    } else if all(&[
+        ShipmentStatus::Delivered as u8,
+        ShipmentStatus::Verified as u8,
+        ShipmentStatus::ReturnInTransit as u8,
+        ShipmentStatus::Returned as u8,
+    ]) {
+        ShipmentStatus::Delivered as u8
+    } else {
+        ShipmentStatus::InTransit as u8
+    }
+}
+
 // Helper methods for converting between enum and u8
 pub trait StatusConversion {
     fn to_u8(&self) -> u8;
@@ -287,6 +761,10 @@ impl StatusConversion for ShipmentStatus {
             2 => Some(ShipmentStatus::Exception),
             3 => Some(ShipmentStatus::Delivered),
             4 => Some(ShipmentStatus::Verified),
+            5 => Some(ShipmentStatus::Canceled),
+            6 => Some(ShipmentStatus::Returned),
+            7 => Some(ShipmentStatus::ReturnInTransit),
+            8 => Some(ShipmentStatus::Lost),
             _ => None,
         }
     }
@@ -308,3 +786,147 @@ impl StatusConversion for AgreementStatus {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two checkpoints about 11 km apart along the equator
+    fn route_plan(next_checkpoint: u8) -> RoutePlan {
+        RoutePlan {
+            checkpoints: vec![
+                RouteCheckpoint {
+                    latitude: 0.0,
+                    longitude: 0.0,
+                    radius_meters: 200,
+                    window_start: 1_000,
+                    window_end: 2_000,
+                },
+                RouteCheckpoint {
+                    latitude: 0.0,
+                    longitude: 0.1,
+                    radius_meters: 200,
+                    window_start: 3_000,
+                    window_end: 4_000,
+                },
+            ],
+            corridor_meters: 500,
+            next_checkpoint,
+        }
+    }
+
+    fn blank_supplier() -> Supplier {
+        Supplier::deserialize(&mut vec![0u8; Supplier::space() - 8].as_slice()).unwrap()
+    }
+
+    #[test]
+    fn rating_rounds_half_up() {
+        let mut supplier = blank_supplier();
+
+        supplier.record_rating(4).unwrap();
+        supplier.record_rating(5).unwrap();
+
+        assert_eq!(supplier.rating, 5);
+        assert_eq!(supplier.rating_average, 450);
+    }
+
+    #[test]
+    fn rating_rounds_down_below_half() {
+        let mut supplier = blank_supplier();
+
+        for rating in [4, 4, 5] {
+            supplier.record_rating(rating).unwrap();
+        }
+
+        assert_eq!(supplier.rating, 4);
+        assert_eq!(supplier.rating_average, 433);
+    }
+
+    #[test]
+    fn on_time_rate_starts_at_zero() {
+        let supplier = blank_supplier();
+
+        assert_eq!(supplier.shipments_delivered, 0);
+        assert_eq!(supplier.on_time_bps, 0);
+    }
+
+    #[test]
+    fn single_late_delivery_has_no_on_time_rate() {
+        let mut supplier = blank_supplier();
+
+        supplier.record_delivery(false).unwrap();
+
+        assert_eq!(supplier.shipments_delivered, 1);
+        assert_eq!(supplier.on_time_bps, 0);
+    }
+
+    #[test]
+    fn single_on_time_delivery_is_fully_on_time() {
+        let mut supplier = blank_supplier();
+
+        supplier.record_delivery(true).unwrap();
+
+        assert_eq!(supplier.on_time_deliveries, 1);
+        assert_eq!(supplier.on_time_bps, 10_000);
+    }
+
+    #[test]
+    fn fix_inside_geofence_during_window_reaches_checkpoint() {
+        let mut plan = route_plan(0);
+
+        let check = plan.check_fix(0.0, 0.0005, 1_500).unwrap();
+
+        assert!(matches!(check, RouteCheck::CheckpointReached(0)));
+        assert_eq!(plan.next_checkpoint, 1);
+    }
+
+    #[test]
+    fn fix_after_window_closes_misses_checkpoint() {
+        let mut plan = route_plan(0);
+
+        let check = plan.check_fix(0.0, 0.0005, 2_001).unwrap();
+
+        assert!(matches!(check, RouteCheck::MissedWindow(0)));
+        assert_eq!(plan.next_checkpoint, 1);
+    }
+
+    #[test]
+    fn fix_outside_corridor_deviates() {
+        let mut plan = route_plan(1);
+
+        let check = plan.check_fix(0.02, 0.05, 3_500).unwrap();
+
+        assert!(matches!(check, RouteCheck::Deviated { distance_meters } if distance_meters > 2_000));
+        assert_eq!(plan.next_checkpoint, 1);
+    }
+
+    #[test]
+    fn fix_inside_corridor_stays_on_route() {
+        let mut plan = route_plan(1);
+
+        let check = plan.check_fix(0.001, 0.05, 3_500).unwrap();
+
+        assert!(matches!(check, RouteCheck::OnRoute));
+    }
+
+    #[test]
+    fn non_finite_fix_is_rejected() {
+        let mut plan = route_plan(0);
+
+        let result = plan.check_fix(f64::NAN, 0.0, 1_500);
+
+        assert_eq!(result.err(), Some(SupplyChainError::InvalidData.into()));
+        assert!(!is_valid_coordinate(0.0, f64::INFINITY));
+        assert!(!is_valid_coordinate(90.5, 0.0));
+    }
+
+    #[test]
+    fn distance_to_leg_is_measured_across_the_leg() {
+        let plan = route_plan(1);
+
+        // 0.01 degrees of latitude is about 1.1 km
+        let distance = distance_to_leg_meters(0.01, 0.05, &plan.checkpoints[0], &plan.checkpoints[1]);
+
+        assert!((distance - 1_112.0).abs() < 5.0);
+    }
+}
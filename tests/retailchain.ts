@@ -10,15 +10,46 @@ describe("retailchain", () => {
   anchor.setProvider(provider);
 
   const program = anchor.workspace.Retailchain as Program<Retailchain>;
-  
+
+  const [config] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+  const [treasury] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("treasury")],
+    program.programId
+  );
+  const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    anchor.web3.BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+  );
+
+  const store = anchor.web3.Keypair.generate();
+  const product = anchor.web3.Keypair.generate();
+
+  // Every instruction checks the program config, which only the upgrade authority
+  // can create
+  before(async () => {
+    await program.methods
+      .initializeConfig(0)
+      .accountsPartial({
+        authority: provider.wallet.publicKey,
+        config,
+        treasury,
+        program: program.programId,
+        programData,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+  });
+
   it("Can initialize a store", async () => {
-    const store = anchor.web3.Keypair.generate();
-    
     await program.methods
       .initializeStore("Test Store", "Test Location")
-      .accounts({
+      .accountsPartial({
         owner: provider.wallet.publicKey,
         store: store.publicKey,
+        config,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
       .signers([store])
@@ -32,26 +63,14 @@ describe("retailchain", () => {
   });
 
   it("Can add a product to store", async () => {
-    // First initialize a store
-    const store = anchor.web3.Keypair.generate();
-    await program.methods
-      .initializeStore("Test Store", "Test Location")
-      .accounts({
-        owner: provider.wallet.publicKey,
-        store: store.publicKey,
-        systemProgram: anchor.web3.SystemProgram.programId,
-      })
-      .signers([store])
-      .rpc();
-
-    // Then add a product
-    const product = anchor.web3.Keypair.generate();
     await program.methods
       .addProduct("Test Product", "Test Description", new anchor.BN(100), new anchor.BN(10))
-      .accounts({
-        owner: provider.wallet.publicKey,
+      .accountsPartial({
+        authority: provider.wallet.publicKey,
         store: store.publicKey,
+        storeMember: null,
         product: product.publicKey,
+        config,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
       .signers([product])
@@ -65,17 +84,14 @@ describe("retailchain", () => {
   });
 
   it("Can update a product", async () => {
-    // First create store and product (similar to above)
-    // Then update the product
-    const newPrice = new anchor.BN(150);
-    const newQuantity = new anchor.BN(5);
-
     await program.methods
-      .updateProduct(newPrice, newQuantity)
-      .accounts({
-        owner: provider.wallet.publicKey,
+      .updateProduct(new anchor.BN(150), new anchor.BN(5))
+      .accountsPartial({
+        authority: provider.wallet.publicKey,
         store: store.publicKey,
+        storeMember: null,
         product: product.publicKey,
+        config,
       })
       .rpc();
 
@@ -83,4 +99,36 @@ describe("retailchain", () => {
     assert.equal(updatedProduct.price.toNumber(), 150);
     assert.equal(updatedProduct.quantity.toNumber(), 5);
   });
-});
\ No newline at end of file
+
+  it("Registers one supplier per authority", async () => {
+    const [supplier] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("supplier"), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const register = () =>
+      program.methods
+        .registerSupplier("Test Supplier", "ISO 9001", "Test Description")
+        .accountsPartial({
+          authority: provider.wallet.publicKey,
+          supplier,
+          config,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    await register();
+
+    const supplierAccount = await program.account.supplier.fetch(supplier);
+    assert.equal(supplierAccount.name, "Test Supplier");
+    assert.ok(supplierAccount.key.equals(provider.wallet.publicKey));
+
+    // The supplier address is derived from the authority, so a second registration fails
+    try {
+      await register();
+      assert.fail("second registration should fail");
+    } catch (err) {
+      assert.notEqual(err.message, "second registration should fail");
+    }
+  });
+});